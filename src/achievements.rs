@@ -0,0 +1,204 @@
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+
+use crate::events::{Flapped, GameReset, PipePassed};
+use crate::ui::toast::Toasts;
+use crate::*;
+
+/// Achievements and how to earn them, loaded from `assets/achievements/*.achievements.ron`
+#[derive(Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "9e41c7d2-5b6a-4f0f-8c3e-1d27a5b9e804"]
+pub struct AchievementList {
+    pub achievements: Vec<Achievement>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub condition: AchievementCondition,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum AchievementCondition {
+    Score(i32),           // points in a single run
+    GamesPlayed(u32),     // runs started in total
+    CalmPipes(u32),       // pipes in a row with at most one flap between each
+    BeatHighScoreBy(i32), // points above the high score the run started with
+    CoinsCollected(u32),  // coins picked up in total
+    LevelStars(u32),      // best stars summed over all levels
+    DailyStreak(u32),     // daily challenges played on consecutive days
+}
+
+/// What the current run has done so far, followed from the gameplay events
+#[derive(Default)]
+struct RunProgress {
+    score: i32,
+    flaps_since_pipe: u32,
+    calm_pipes: u32,
+    start_high_score: i32,
+}
+
+impl RunProgress {
+    fn reset(&mut self, game_controller: &GameController) {
+        *self = RunProgress {
+            start_high_score: game_controller.player_stats.high_score,
+            ..Default::default()
+        };
+    }
+
+    fn flapped(&mut self) {
+        self.flaps_since_pipe += 1;
+    }
+
+    fn pipe_passed(&mut self, score: i32) {
+        self.score = score;
+
+        self.calm_pipes = if self.flaps_since_pipe <= 1 {
+            self.calm_pipes + 1
+        } else {
+            0
+        };
+        self.flaps_since_pipe = 0;
+    }
+}
+
+impl AchievementCondition {
+    fn is_met(&self, run: &RunProgress, stats: &PlayerStatistics) -> bool {
+        match *self {
+            AchievementCondition::Score(score) => run.score >= score,
+            AchievementCondition::GamesPlayed(games) => stats.games_played >= games,
+            AchievementCondition::CalmPipes(pipes) => run.calm_pipes >= pipes,
+            AchievementCondition::BeatHighScoreBy(points) => {
+                run.start_high_score > 0 && run.score >= run.start_high_score + points
+            }
+            AchievementCondition::CoinsCollected(coins) => stats.coins_collected >= coins,
+            AchievementCondition::LevelStars(stars) => {
+                stats
+                    .level_stars
+                    .values()
+                    .map(|level| *level as u32)
+                    .sum::<u32>()
+                    >= stars
+            }
+            AchievementCondition::DailyStreak(days) => stats.daily_streak >= days,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AchievementListLoader;
+
+impl AssetLoader for AchievementListLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let list: AchievementList = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(list));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["achievements.ron"]
+    }
+}
+
+pub struct AchievementsHandler {
+    pub list: Handle<AchievementList>,
+}
+
+impl FromWorld for AchievementsHandler {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
+
+        AchievementsHandler {
+            list: asset_server.load(ACHIEVEMENTS_PATH),
+        }
+    }
+}
+
+fn achievements_system(
+    mut run: Local<RunProgress>,
+    (mut reset_events, mut flap_events, mut passed_events): (
+        EventReader<GameReset>,
+        EventReader<Flapped>,
+        EventReader<PipePassed>,
+    ),
+
+    mut pkv: ResMut<PkvStore>,
+    mut toasts: ResMut<Toasts>,
+    mut game_controller: ResMut<GameController>,
+    achievements_handler: Res<AchievementsHandler>,
+    lists: Res<Assets<AchievementList>>,
+) {
+    for _ in reset_events.iter() {
+        run.reset(&game_controller);
+    }
+    for _ in flap_events.iter() {
+        // the first flap starts the run
+        if game_controller.flaps == 1 {
+            run.reset(&game_controller);
+        }
+        run.flapped();
+    }
+    for passed in passed_events.iter() {
+        run.pipe_passed(passed.score);
+    }
+
+    // runs played for someone else, or in the editor, do not count
+    if matches!(game_controller.mode, GameMode::Spectator | GameMode::Editor) {
+        return;
+    }
+
+    let list = match lists.get(&achievements_handler.list) {
+        Some(list) => list,
+        None => return,
+    };
+
+    let mut unlocked = false;
+    for achievement in &list.achievements {
+        let stats = &game_controller.player_stats;
+        if stats.achievements.contains(&achievement.id)
+            || !achievement.condition.is_met(&run, stats)
+        {
+            continue;
+        }
+
+        info!("Achievement unlocked: {}", achievement.name);
+        toasts.push(
+            &format!("Achievement: {}", achievement.name),
+            &achievement.description,
+        );
+
+        // skins rewarded for it are owned from now on
+        game_controller
+            .player_stats
+            .achievements
+            .insert(achievement.id.clone());
+
+        unlocked = true;
+    }
+
+    if unlocked {
+        game_controller.save_player_stats(&mut pkv);
+    }
+}
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<AchievementList>()
+            .init_asset_loader::<AchievementListLoader>()
+            .init_resource::<AchievementsHandler>()
+            .add_system(achievements_system);
+    }
+}
@@ -1,13 +1,13 @@
 use bevy::prelude::*;
 
-mod clouds;
-mod hills;
+pub mod parallax;
+pub mod sky;
 
 pub struct BackgroundPlugin;
 
 impl Plugin for BackgroundPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(clouds::CloudsPlugin)
-            .add_plugin(hills::HillsPlugin);
+        app.add_plugin(parallax::ParallaxPlugin)
+            .add_plugin(sky::SkyPlugin);
     }
 }
@@ -0,0 +1,190 @@
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use rand::prelude::*;
+use serde::Deserialize;
+
+use crate::{game_controller::GameController, options::*};
+
+/// Background layers scrolling behind the pipes, loaded from `assets/backgrounds/*.parallax.ron`
+#[derive(Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "d4a81f36-2e9c-4b57-a0f3-6c95e1b7d240"]
+pub struct ParallaxLayers {
+    pub layers: Vec<ParallaxLayer>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ParallaxLayer {
+    pub name: String,
+    pub pieces: Vec<Vec<String>>, // sprites of a piece from left to right, one piece is picked per spawn
+    pub tile_width: f32,
+    pub speed: f32, // relative to the pipes, negative scrolls to the right
+    pub y_range: (f32, f32),
+    pub spacing: f32, // between the pieces, the smaller the denser
+    pub z: f32,
+}
+
+#[derive(Default)]
+pub struct ParallaxLayersLoader;
+
+impl AssetLoader for ParallaxLayersLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let layers: ParallaxLayers = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(layers));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["parallax.ron"]
+    }
+}
+
+pub struct ParallaxHandler {
+    pub layers: Handle<ParallaxLayers>, // swapped for the active theme once it is loaded
+}
+
+impl FromWorld for ParallaxHandler {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
+
+        ParallaxHandler {
+            layers: asset_server.load(CLASSIC_PARALLAX_PATH),
+        }
+    }
+}
+
+#[derive(Component)]
+struct ParallaxPiece {
+    speed: f32,
+    y_range: (f32, f32),
+    span: f32, // covered by all pieces of the layer, a piece leaving one side wraps around by it
+}
+
+#[derive(Component)]
+pub struct ParallaxBlock;
+
+impl ParallaxPiece {
+    fn roll_y(&self) -> f32 {
+        thread_rng().gen_range(self.y_range.0..=self.y_range.1)
+    }
+}
+
+fn spawn_layer(commands: &mut Commands, asset_server: &AssetServer, layer: &ParallaxLayer) {
+    if layer.pieces.is_empty() || layer.spacing <= 0.0 {
+        warn!("Parallax layer {} has nothing to spawn", layer.name);
+        return;
+    }
+
+    let mut rng = thread_rng();
+    let count = (2.0 * SCREEN_X_BOUNDARY / layer.spacing).ceil() as usize + 1;
+
+    for i in 0..count {
+        let piece = ParallaxPiece {
+            speed: layer.speed,
+            y_range: layer.y_range,
+            span: count as f32 * layer.spacing,
+        };
+        let x = -SCREEN_X_BOUNDARY + i as f32 * layer.spacing;
+        let sprites = layer.pieces.choose(&mut rng).unwrap();
+
+        commands
+            .spawn_bundle(SpatialBundle::from_transform(Transform::from_xyz(
+                x,
+                piece.roll_y(),
+                layer.z,
+            )))
+            .with_children(|parent| {
+                for (tile, sprite) in sprites.iter().enumerate() {
+                    parent
+                        .spawn_bundle(SpriteBundle {
+                            texture: asset_server.load(sprite.as_str()),
+                            transform: Transform::from_xyz(
+                                tile as f32 * layer.tile_width,
+                                0.0,
+                                0.0,
+                            ),
+                            ..Default::default()
+                        })
+                        .insert(ParallaxBlock);
+                }
+            })
+            .insert(piece);
+    }
+}
+
+// (re)spawn the layers whenever their file is loaded, changed or swapped by a theme
+fn parallax_setup_system(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<ParallaxLayers>>,
+    pieces_query: Query<Entity, With<ParallaxPiece>>,
+    asset_server: Res<AssetServer>,
+    parallax_handler: Res<ParallaxHandler>,
+    layers: Res<Assets<ParallaxLayers>>,
+) {
+    let mut changed = parallax_handler.is_changed();
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                changed |= *handle == parallax_handler.layers;
+            }
+            AssetEvent::Removed { .. } => {}
+        }
+    }
+
+    let layers = match layers.get(&parallax_handler.layers) {
+        Some(layers) if changed => layers,
+        _ => return,
+    };
+
+    for entity in pieces_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for layer in &layers.layers {
+        spawn_layer(&mut commands, &asset_server, layer);
+    }
+}
+
+fn parallax_system(
+    mut query: Query<(&ParallaxPiece, &mut Transform)>,
+    game_controller: Res<GameController>,
+    time: Res<Time>,
+) {
+    if game_controller.is_game_paused() {
+        return;
+    }
+
+    let delta_time = game_controller.delta_time(&time);
+
+    // the blocks follow the piece transform
+    for (piece, mut transform) in query.iter_mut() {
+        transform.translation.x -= PIPES_SPEED * piece.speed * delta_time;
+
+        let left = -SCREEN_X_BOUNDARY;
+        if transform.translation.x < left {
+            transform.translation.x += piece.span;
+            transform.translation.y = piece.roll_y();
+        } else if transform.translation.x > left + piece.span {
+            transform.translation.x -= piece.span;
+            transform.translation.y = piece.roll_y();
+        }
+    }
+}
+
+pub struct ParallaxPlugin;
+
+impl Plugin for ParallaxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ParallaxLayers>()
+            .init_asset_loader::<ParallaxLayersLoader>()
+            .init_resource::<ParallaxHandler>()
+            .add_system(parallax_setup_system)
+            .add_system(parallax_system);
+    }
+}
@@ -0,0 +1,173 @@
+use bevy::prelude::*;
+use chrono::{Local, Timelike};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+use crate::background::parallax::ParallaxBlock;
+use crate::themes::{Theme, ThemesHandler};
+use crate::ui::HudText;
+use crate::{game_controller::GameController, options::*};
+
+/// What moves the sun across the sky
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SkyMode {
+    Time,  // a day every `SKY_DAY_SECS` of play
+    Score, // a day every `SKY_DAY_SCORE` points, every run starts at noon
+    Clock, // the local time of day
+}
+
+impl Default for SkyMode {
+    fn default() -> Self {
+        SkyMode::Time
+    }
+}
+
+impl SkyMode {
+    pub fn next(&self) -> Self {
+        match self {
+            SkyMode::Time => SkyMode::Score,
+            SkyMode::Score => SkyMode::Clock,
+            SkyMode::Clock => SkyMode::Time,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SkyMode::Time => "Time",
+            SkyMode::Score => "Score",
+            SkyMode::Clock => "Clock",
+        }
+    }
+}
+
+/// Colors of a time of day, in 0-255
+#[derive(Deserialize, Clone, Debug)]
+pub struct SkyPalette {
+    pub sky: [f32; 3],
+    pub tint: [f32; 3], // of the background layers
+    pub stars: f32,     // opacity
+}
+
+impl SkyPalette {
+    /// Palette at `phase` of the day, blended between the `palettes` spread over it
+    fn blend(palettes: &[SkyPalette], phase: f32) -> (Color, Color, f32) {
+        let position = phase.rem_euclid(1.0) * palettes.len() as f32;
+        let from = &palettes[position as usize % palettes.len()];
+        let to = &palettes[(position as usize + 1) % palettes.len()];
+        let t = position.fract();
+
+        let mix = |a: [f32; 3], b: [f32; 3]| {
+            Color::rgb(
+                (a[0] + (b[0] - a[0]) * t) / 255.0,
+                (a[1] + (b[1] - a[1]) * t) / 255.0,
+                (a[2] + (b[2] - a[2]) * t) / 255.0,
+            )
+        };
+
+        (
+            mix(from.sky, to.sky),
+            mix(from.tint, to.tint),
+            from.stars + (to.stars - from.stars) * t,
+        )
+    }
+}
+
+#[derive(Component)]
+struct Star {
+    twinkle_offset: f32,
+}
+
+fn stars_setup(mut commands: Commands) {
+    let mut rng = thread_rng();
+
+    for _ in 0..SKY_STARS {
+        let size = rng.gen_range(2.0..4.0);
+
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.0),
+                    custom_size: Some(Vec2::splat(size)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(
+                    rng.gen_range(-WINDOW_WIDTH / 2.0..WINDOW_WIDTH / 2.0),
+                    rng.gen_range(-50.0..WINDOW_HEIGHT / 2.0),
+                    Z_STARS,
+                ),
+                ..Default::default()
+            })
+            .insert(Star {
+                twinkle_offset: rng.gen_range(0.0..TAU),
+            });
+    }
+}
+
+fn sky_system(
+    mut clear_color: ResMut<ClearColor>,
+    mut blocks_query: Query<&mut Sprite, (With<ParallaxBlock>, Without<Star>)>,
+    mut stars_query: Query<(&mut Sprite, &Star)>,
+    mut played_secs: Local<f32>,
+    (themes_handler, themes): (Res<ThemesHandler>, Res<Assets<Theme>>),
+    game_controller: Res<GameController>,
+    time: Res<Time>,
+) {
+    if !game_controller.is_game_paused() {
+        *played_secs += game_controller.delta_time(&time);
+    }
+
+    // a phase of 0 is dawn, the day starts at noon
+    let phase = match game_controller.settings.sky_mode {
+        SkyMode::Time => 0.25 + *played_secs / SKY_DAY_SECS,
+        SkyMode::Score => 0.25 + game_controller.score as f32 / SKY_DAY_SCORE,
+        SkyMode::Clock => {
+            let now = Local::now();
+            (now.hour() as f32 + now.minute() as f32 / 60.0 - 6.0) / 24.0
+        }
+    };
+    let palettes = match themes_handler.active(&themes, &game_controller.settings) {
+        Some(theme) => theme.palettes(),
+        None => SKY_PALETTES.to_vec(),
+    };
+    let (sky, tint, stars) = SkyPalette::blend(&palettes, phase);
+
+    clear_color.0 = sky;
+
+    for mut sprite in blocks_query.iter_mut() {
+        sprite.color = tint;
+    }
+
+    let twinkle_time = time.seconds_since_startup() as f32 * 2.0;
+    for (mut sprite, star) in stars_query.iter_mut() {
+        let twinkle = 0.7 + 0.3 * (twinkle_time + star.twinkle_offset).sin();
+        sprite.color.set_a(stars * twinkle);
+    }
+}
+
+// dark text on a bright sky, light text once it gets dark
+fn hud_ink_system(mut text_query: Query<&mut Text, With<HudText>>, clear_color: Res<ClearColor>) {
+    let sky = clear_color.0;
+    let luminance = 0.299 * sky.r() + 0.587 * sky.g() + 0.114 * sky.b();
+    let ink = if luminance < 0.45 {
+        Color::WHITE
+    } else {
+        Color::BLACK
+    };
+
+    for mut text in text_query.iter_mut() {
+        if text.sections[0].style.color != ink {
+            text.sections[0].style.color = ink;
+        }
+    }
+}
+
+pub struct SkyPlugin;
+
+impl Plugin for SkyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(stars_setup)
+            .add_system(sky_system)
+            .add_system(hud_ink_system.after(sky_system));
+    }
+}
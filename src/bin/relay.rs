@@ -0,0 +1,141 @@
+//! Relay server for versus mode.
+//!
+//! Pairs up clients that send a `join` message, hands both of them the same
+//! seed and forwards every other message to the opponent.
+//!
+//! Usage: `cargo run --bin relay [address]` (defaults to 127.0.0.1:7878)
+
+use std::collections::VecDeque;
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[allow(dead_code)]
+#[path = "../network/connection.rs"]
+mod connection;
+#[allow(dead_code)]
+#[path = "../network/protocol.rs"]
+mod protocol;
+
+use connection::LineConnection;
+use protocol::NetMessage;
+
+static DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+struct Client {
+    id: u32,
+    connection: LineConnection,
+    opponent: Option<u32>,
+}
+
+fn new_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+fn main() {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+
+    let listener = TcpListener::bind(&address).expect("Failed to bind relay address");
+    listener
+        .set_nonblocking(true)
+        .expect("Failed to set listener to non-blocking");
+    println!("Relay listening on {}", address);
+
+    let mut clients: Vec<Client> = Vec::new();
+    let mut lobby: VecDeque<u32> = VecDeque::new();
+    let mut next_id = 0;
+
+    loop {
+        // accept new clients
+        while let Ok((stream, peer)) = listener.accept() {
+            match LineConnection::new(stream) {
+                Ok(connection) => {
+                    println!("Client {} connected from {}", next_id, peer);
+                    clients.push(Client {
+                        id: next_id,
+                        connection,
+                        opponent: None,
+                    });
+                    next_id += 1;
+                }
+                Err(e) => println!("Failed to set up connection from {}: {}", peer, e),
+            }
+        }
+
+        // collect messages
+        let mut forwards: Vec<(u32, String)> = Vec::new();
+        for client in clients.iter_mut() {
+            for line in client.connection.receive_lines() {
+                match NetMessage::from_line(&line) {
+                    Some(NetMessage::Join) => {
+                        if client.opponent.is_none() && !lobby.contains(&client.id) {
+                            lobby.push_back(client.id);
+                        }
+                    }
+                    Some(_) => {
+                        if let Some(opponent) = client.opponent {
+                            forwards.push((opponent, line));
+                        }
+                    }
+                    None => println!("Client {} sent an invalid message: {}", client.id, line),
+                }
+            }
+        }
+
+        for (id, line) in forwards {
+            if let Some(client) = clients.iter_mut().find(|client| client.id == id) {
+                client.connection.send_line(&format!("{}\n", line));
+            }
+        }
+
+        // drop disconnected clients and notify their opponents
+        let disconnected: Vec<(u32, Option<u32>)> = clients
+            .iter()
+            .filter(|client| client.connection.closed)
+            .map(|client| (client.id, client.opponent))
+            .collect();
+        for (id, opponent) in disconnected {
+            println!("Client {} disconnected", id);
+            clients.retain(|client| client.id != id);
+            lobby.retain(|lobby_id| *lobby_id != id);
+
+            if let Some(client) = clients
+                .iter_mut()
+                .find(|client| Some(client.id) == opponent)
+            {
+                client.opponent = None;
+                client.connection.send(&NetMessage::OpponentLeft);
+            }
+        }
+
+        // pair up waiting clients
+        while lobby.len() >= 2 {
+            let first = lobby.pop_front().unwrap();
+            let second = lobby.pop_front().unwrap();
+            let seed = new_seed();
+
+            println!("Starting match between {} and {} (seed {})", first, second, seed);
+            for client in clients.iter_mut() {
+                if client.id == first {
+                    client.opponent = Some(second);
+                } else if client.id == second {
+                    client.opponent = Some(first);
+                } else {
+                    continue;
+                }
+                client.connection.send(&NetMessage::Start { seed });
+            }
+        }
+
+        for client in clients.iter_mut() {
+            client.connection.flush();
+        }
+
+        thread::sleep(Duration::from_millis(5));
+    }
+}
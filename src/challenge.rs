@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+
+use crate::ui::{HudText, UiZ};
+use crate::*;
+
+#[derive(Component)]
+struct ChallengeTimerText;
+
+fn challenge_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load(FONT_PATH),
+                    font_size: 40.0,
+                    color: Color::BLACK,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(5.0),
+                    left: Val::Percent(45.0),
+                    ..Default::default()
+                },
+                ..default()
+            }),
+        )
+        .insert(ChallengeTimerText)
+        .insert(HudText)
+        .insert(UiZ(20.0));
+}
+
+fn challenge_system(
+    mut game_controller: ResMut<GameController>,
+    mut player_query: Query<&mut Player>,
+    mut pkv: ResMut<PkvStore>,
+    time: Res<Time>,
+) {
+    if !game_controller.is_game_running() {
+        return;
+    }
+
+    game_controller.run_time += time.delta_seconds();
+
+    let goal_reached = match game_controller.mode {
+        GameMode::TimeAttack => game_controller.run_time >= TIME_ATTACK_SECS,
+        GameMode::PipeRush => game_controller.score >= PIPE_RUSH_PIPES,
+        GameMode::Levels | GameMode::Editor => game_controller.level_finished(),
+        _ => false,
+    };
+
+    if goal_reached {
+        game_controller.win_challenge(&mut pkv);
+
+        // keep the bird in place on the results screen
+        let mut player = player_query.single_mut();
+        player.delta_y = 0.0;
+    }
+}
+
+fn challenge_timer_system(
+    mut text_query: Query<(&mut Text, &mut Visibility), With<ChallengeTimerText>>,
+    game_controller: Res<GameController>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let (mut text, mut visibility) = text_query.single_mut();
+    let player_transform = player_query.single();
+
+    visibility.is_visible = game_controller.mode.is_challenge()
+        && game_controller.has_game_started()
+        && !game_controller.is_game_finished(player_transform);
+
+    text.sections[0].value = match game_controller.mode {
+        GameMode::PipeRush => format!(
+            "{}/{}  {:.1}s",
+            game_controller.score, PIPE_RUSH_PIPES, game_controller.run_time
+        ),
+        _ => format!(
+            "{:.1}s",
+            (TIME_ATTACK_SECS - game_controller.run_time).max(0.0)
+        ),
+    };
+}
+
+pub struct ChallengePlugin;
+
+impl Plugin for ChallengePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(challenge_setup)
+            .add_system(challenge_system)
+            .add_system(challenge_timer_system);
+    }
+}
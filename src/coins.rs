@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+
+use crate::particles;
+use crate::powerups::Magnet;
+use crate::sound::{EffectsChannel, EffectsHandler, SoundEffect};
+use crate::*;
+
+const COIN_PARTICLES: usize = 12;
+
+/// Coin in a pipe gap, a hidden child of the pipe once collected
+#[derive(Component)]
+pub struct Coin {
+    pub collected: bool,
+}
+
+fn coins_system(
+    mut commands: Commands,
+    mut coin_query: Query<(&mut Coin, &mut Visibility, &mut Transform, &GlobalTransform)>,
+    player_query: Query<(&Player, &Transform, Option<&Magnet>), Without<Coin>>,
+    mut game_controller: ResMut<GameController>,
+    mut pkv: ResMut<PkvStore>,
+    (effects_handler, effects): (Res<EffectsHandler>, Res<AudioChannel<EffectsChannel>>),
+    time: Res<Time>,
+) {
+    if !game_controller.is_game_running() {
+        return;
+    }
+    // borrow the settings and the sound rng apart
+    let game_controller = &mut *game_controller;
+
+    let (player, player_transform, magnet) = player_query.single();
+    if player.dead {
+        return;
+    }
+    let player_position = player_transform.translation.truncate();
+    let delta_time = game_controller.delta_time(&time);
+
+    for (mut coin, mut visibility, mut transform, global_transform) in coin_query.iter_mut() {
+        if coin.collected {
+            continue;
+        }
+
+        let mut position = global_transform.translation().truncate();
+
+        // the pipes are not rotated or scaled, so the coin moves the same in its local space
+        let to_player = player_position - position;
+        if magnet.is_some() && to_player.length() < MAGNET_RADIUS {
+            let pull = to_player.clamp_length_max(MAGNET_PULL_SPEED * delta_time);
+            transform.translation += pull.extend(0.0);
+            position += pull;
+        }
+
+        if position.distance(player_position) < COIN_PICKUP_RADIUS {
+            coin.collected = true;
+            visibility.is_visible = false;
+            game_controller.collect_coin(&mut pkv);
+
+            effects_handler.play(
+                &effects,
+                SoundEffect::Coin,
+                &game_controller.settings,
+                &mut game_controller.sound_rng,
+            );
+
+            let color = Color::rgb(
+                COIN_COLOR[0] / 255.0,
+                COIN_COLOR[1] / 255.0,
+                COIN_COLOR[2] / 255.0,
+            );
+            particles::spawn_burst(&mut commands, position, color, COIN_PARTICLES);
+        }
+    }
+}
+
+pub struct CoinsPlugin;
+
+impl Plugin for CoinsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(coins_system.after(pipes_system));
+    }
+}
@@ -0,0 +1,22 @@
+use chrono::{Duration, Local, NaiveDate};
+
+static DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Today's date in the player's local time, e.g. "2022-08-30"
+pub fn today() -> String {
+    Local::now().format(DATE_FORMAT).to_string()
+}
+
+pub fn yesterday(date: &str) -> Option<String> {
+    let date = NaiveDate::parse_from_str(date, DATE_FORMAT).ok()?;
+
+    Some((date - Duration::days(1)).format(DATE_FORMAT).to_string())
+}
+
+/// Seed shared by everyone playing the daily challenge on `date`
+pub fn daily_seed(date: &str) -> u64 {
+    // FNV-1a, so the seed is the same on every platform and build
+    date.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,51 @@
+use rand::prelude::*;
+use std::f32::consts::{E, TAU};
+
+use crate::network::protocol::PipeMovement;
+use crate::*;
+
+/// How the pipes get harder as the score climbs
+pub struct DifficultyProfile {
+    pub narrow_gap_scores: [i32; 2], // the gap shrinks by a sprite past each score
+    pub moving_pipes_score: i32,
+    pub moving_pipes_chance: f32,
+    pub moving_amplitude_spr: f32,
+    pub moving_period_secs: f32,
+}
+
+impl DifficultyProfile {
+    /// Pipe speed once `score` pipes have been passed
+    pub fn speed_multiplier(&self, score: i32) -> f32 {
+        let a = 0.5;
+        let b = -0.5;
+        let c = 25.0;
+
+        1.0 / (a + E.powf(-(score as f32 / c) + b))
+    }
+
+    pub fn gap_sprites(&self, score: i32) -> u32 {
+        let narrowed = self
+            .narrow_gap_scores
+            .iter()
+            .filter(|narrow_score| score > **narrow_score)
+            .count() as u32;
+
+        PIPE_Y_GAP_SPR - narrowed
+    }
+
+    pub fn roll_movement(&self, score: i32, rng: &mut StdRng) -> Option<PipeMovement> {
+        // always roll, so clients sharing a seed draw the same numbers regardless of score
+        let roll: f32 = rng.gen();
+        let phase = rng.gen_range(0.0..TAU);
+
+        if score < self.moving_pipes_score || roll > self.moving_pipes_chance {
+            return None;
+        }
+
+        Some(PipeMovement {
+            amplitude: self.moving_amplitude_spr * SPRITE_SIZE,
+            period: self.moving_period_secs,
+            phase,
+        })
+    }
+}
@@ -0,0 +1,559 @@
+use bevy::asset::LoadState;
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+
+use crate::events::RestartRequested;
+use crate::levels::{Level, LevelPipe, LevelPipeMovement, LevelsHandler};
+use crate::ui::{HudText, UiZ};
+use crate::*;
+
+/// Level being edited and the pipes previewing it
+#[derive(Default)]
+pub struct LevelEditor {
+    level: Option<Level>,
+    level_index: usize,
+    scroll: f32, // same as `GameController::distance` when test playing from here
+    selected: Option<usize>,
+    drag_offset: Option<(f32, i32)>, // cursor relative to the dragged pipe and its gap
+    testing: bool,
+    changed: bool,       // the preview has to be rebuilt
+    default_stars: bool, // the level sets no star thresholds, they follow its pipe count
+    status: String,
+    preview: Vec<PreviewPipe>, // kept hidden while unused, like the blocks of the pipes
+}
+
+/// Pipe entity of the preview, the blocks are its children
+struct PreviewPipe {
+    entity: Entity,
+    pipe: PipeParent,
+    shown: Option<(usize, LevelPipe)>, // level entry the blocks are built for
+}
+
+#[derive(Component)]
+struct EditorText;
+
+fn editor_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load(FONT_PATH),
+                    font_size: 25.0,
+                    color: Color::BLACK,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(5.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..default()
+            }),
+        )
+        .insert(EditorText)
+        .insert(HudText)
+        .insert(UiZ(20.0));
+}
+
+/// Load the selected level into the editor and switch between editing and test playing
+fn editor_mode_system(
+    mut editor: ResMut<LevelEditor>,
+    mut game_controller: ResMut<GameController>,
+    mut restart_events: EventWriter<RestartRequested>,
+    keyboard_input: Res<Input<KeyCode>>,
+    levels_handler: Res<LevelsHandler>,
+    levels: Res<Assets<Level>>,
+    asset_server: Res<AssetServer>,
+) {
+    if game_controller.mode != GameMode::Editor {
+        if editor.level.is_some() {
+            editor.level = None;
+            editor.testing = false;
+            editor.changed = true;
+        }
+        return;
+    }
+
+    let level_index = game_controller.level_index;
+    if editor.level.is_none() || editor.level_index != level_index {
+        let handle = &levels_handler.levels[level_index];
+        let level = match levels.get(handle) {
+            Some(level) => level.clone(),
+            None if asset_server.get_load_state(handle) == LoadState::Loading => return,
+            None => empty_level(level_index),
+        };
+
+        *editor = LevelEditor {
+            default_stars: level.two_star_flaps == 0 && level.three_star_flaps == 0,
+            level: Some(level),
+            level_index,
+            changed: true,
+            status: format!("Editing {}", LEVELS[level_index]),
+            preview: std::mem::take(&mut editor.preview),
+            ..Default::default()
+        };
+
+        // park the pipes of whatever was played before
+        game_controller.level = None;
+        game_controller.change_mode(GameMode::Editor, &mut restart_events);
+    }
+
+    if game_controller.is_game_paused() {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        editor.testing = !editor.testing;
+        editor.changed = true;
+
+        if let (true, Some(level)) = (editor.default_stars, editor.level.as_mut()) {
+            default_star_flaps(level);
+        }
+
+        game_controller.level = match editor.testing {
+            true => editor.level.clone().map(|level| Level {
+                start: editor.scroll,
+                ..level
+            }),
+            false => None,
+        };
+        game_controller.change_mode(GameMode::Editor, &mut restart_events);
+    }
+
+    if !editor.testing && game_controller.game_state == GameState::Waiting {
+        game_controller.game_state = GameState::Editing;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn editor_input_system(
+    mut editor: ResMut<LevelEditor>,
+    game_controller: Res<GameController>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    windows: Res<Windows>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    ui_query: Query<&Interaction>,
+    levels_handler: Res<LevelsHandler>,
+    mut levels: ResMut<Assets<Level>>,
+    time: Res<Time>,
+) {
+    let shift = keyboard_input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    let control = keyboard_input.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+
+    // macOS turns the wheel into horizontal scroll while Shift is held
+    let wheel_steps: i32 = wheel_events
+        .iter()
+        .map(|event| if shift { event.y + event.x } else { event.y })
+        .filter(|steps| *steps != 0.0)
+        .map(|steps| steps.signum() as i32)
+        .sum();
+
+    if game_controller.game_state != GameState::Editing {
+        return;
+    }
+
+    let editor = &mut *editor;
+    let level = match &mut editor.level {
+        Some(level) => level,
+        None => return,
+    };
+
+    // scroll along the level
+    let scroll_speed = EDITOR_SCROLL_SPEED * if shift { 3.0 } else { 1.0 };
+    let mut scroll = editor.scroll;
+    if keyboard_input.any_pressed([KeyCode::Left, KeyCode::A]) {
+        scroll -= scroll_speed * time.delta_seconds();
+    }
+    if keyboard_input.any_pressed([KeyCode::Right, KeyCode::D]) {
+        scroll += scroll_speed * time.delta_seconds();
+    }
+    scroll = scroll.clamp(0.0, level.finish_x());
+    if scroll != editor.scroll {
+        editor.scroll = scroll;
+        editor.changed = true;
+    }
+
+    // place, select and drag pipes
+    let over_ui = ui_query
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+    if let Some(cursor) = cursor_world_position(&windows, &camera_query) {
+        let cursor_level_x = (cursor.x - PIPES_START_X + editor.scroll).round();
+
+        if mouse_input.just_pressed(MouseButton::Left) && !over_ui {
+            match pipe_at(level, editor.scroll, cursor.x) {
+                Some(i) => {
+                    let pipe = &level.pipes[i];
+                    let height_offset = height_at(cursor.y, pipe.gap) as i32 - pipe.height as i32;
+
+                    editor.selected = Some(i);
+                    editor.drag_offset = Some((cursor_level_x - level.pipe_x(i), height_offset));
+                }
+                None => {
+                    let pipe = LevelPipe {
+                        spacing: 0.0,
+                        height: height_at(cursor.y, PIPE_Y_GAP_SPR),
+                        gap: PIPE_Y_GAP_SPR,
+                        width: PIPE_WIDTH,
+                        movement: None,
+                    };
+
+                    editor.selected = insert_pipe(level, cursor_level_x, pipe);
+                    editor.drag_offset = editor.selected.map(|_| (0.0, 0));
+                    editor.changed = true;
+
+                    if editor.selected.is_none() {
+                        editor.status = "Too close to another pipe".to_string();
+                    }
+                }
+            }
+        } else if mouse_input.pressed(MouseButton::Left) {
+            if let (Some(i), Some((x_offset, height_offset))) =
+                (editor.selected, editor.drag_offset)
+            {
+                let pipe = &mut level.pipes[i];
+                let height = (height_at(cursor.y, pipe.gap) as i32 - height_offset)
+                    .clamp(0, (PIPE_COLUMN_SPR - pipe.gap) as i32)
+                    as u32;
+                if pipe.height != height {
+                    pipe.height = height;
+                    editor.changed = true;
+                }
+
+                editor.changed |= move_pipe(level, i, cursor_level_x - x_offset);
+            }
+        } else {
+            editor.drag_offset = None;
+        }
+
+        if mouse_input.just_pressed(MouseButton::Right) && !over_ui {
+            if let Some(i) = pipe_at(level, editor.scroll, cursor.x) {
+                remove_pipe(level, i);
+                editor.selected = None;
+                editor.changed = true;
+            }
+        }
+    }
+
+    if let Some(i) = editor.selected {
+        let pipe = &mut level.pipes[i];
+
+        // wheel adjusts the gap height, its size or the pipe width
+        if wheel_steps != 0 {
+            if control {
+                pipe.width =
+                    (pipe.width as i32 + wheel_steps).clamp(1, EDITOR_MAX_PIPE_WIDTH as i32) as u32;
+            } else if shift {
+                pipe.gap = (pipe.gap as i32 + wheel_steps).clamp(1, PIPE_COLUMN_SPR as i32) as u32;
+                pipe.height = pipe.height.min(PIPE_COLUMN_SPR - pipe.gap);
+            } else {
+                pipe.height = (pipe.height as i32 + wheel_steps)
+                    .clamp(0, (PIPE_COLUMN_SPR - pipe.gap) as i32)
+                    as u32;
+            }
+            editor.changed = true;
+        }
+
+        if keyboard_input.just_pressed(KeyCode::M) {
+            pipe.movement = match pipe.movement {
+                Some(_) => None,
+                None => Some(LevelPipeMovement {
+                    amplitude: 1.0,
+                    period: 3.0,
+                    phase: 0.0,
+                }),
+            };
+            editor.changed = true;
+        }
+
+        if keyboard_input.any_just_pressed([KeyCode::Delete, KeyCode::Back]) {
+            remove_pipe(level, i);
+            editor.selected = None;
+            editor.changed = true;
+        }
+    }
+
+    // save to the level file and update the loaded level
+    if control && keyboard_input.just_pressed(KeyCode::S) {
+        let path = LEVELS[editor.level_index];
+
+        if editor.default_stars {
+            default_star_flaps(level);
+        }
+
+        editor.status = match save_level(level, path) {
+            Ok(()) => {
+                levels.set_untracked(&levels_handler.levels[editor.level_index], level.clone());
+                format!("Saved {}", path)
+            }
+            Err(e) => {
+                warn!("Failed to save level {}: {}", path, e);
+                format!("Failed to save {}", path)
+            }
+        };
+    }
+}
+
+/// Show the preview pipes with the same blocks the level is played with,
+/// blocks are only rebuilt for the level entries that changed
+fn editor_preview_system(
+    mut commands: Commands,
+    mut editor: ResMut<LevelEditor>,
+    pipes_handler: Res<PipesHandler>,
+) {
+    if !editor.changed {
+        return;
+    }
+    editor.changed = false;
+
+    let editor = &mut *editor;
+    let on_screen: Vec<(usize, f32)> = match &editor.level {
+        Some(level) if !editor.testing => (0..level.pipes.len())
+            .map(|i| (i, level.pipe_x(i) - editor.scroll + PIPES_START_X))
+            .filter(|(_, x)| (-SCREEN_X_BOUNDARY..SCREEN_X_BOUNDARY).contains(x))
+            .collect(),
+        _ => Vec::new(),
+    };
+    // where the pipe is does not change its blocks
+    let entry = |i: usize| {
+        let level_pipe = &editor.level.as_ref().unwrap().pipes[i];
+        LevelPipe {
+            spacing: 0.0,
+            ..level_pipe.clone()
+        }
+    };
+
+    // free the pipes whose entry left the screen or was edited
+    for preview in editor.preview.iter_mut() {
+        let up_to_date = preview.shown.as_ref().map_or(false, |(i, shown)| {
+            on_screen.iter().any(|(j, _)| j == i) && entry(*i) == *shown
+        });
+        if !up_to_date {
+            preview.shown = None;
+        }
+    }
+
+    for (i, x) in on_screen.iter().copied() {
+        let shown = |preview: &PreviewPipe| preview.shown.as_ref().map(|(j, _)| *j) == Some(i);
+
+        let slot = match editor.preview.iter().position(shown) {
+            Some(slot) => slot,
+            None => {
+                let slot = match editor.preview.iter().position(|p| p.shown.is_none()) {
+                    Some(slot) => slot,
+                    None => {
+                        let entity = commands.spawn_bundle(SpatialBundle::default()).id();
+                        editor.preview.push(PreviewPipe {
+                            entity,
+                            pipe: PipeParent::from_level_pipe(&entry(i)),
+                            shown: None,
+                        });
+                        editor.preview.len() - 1
+                    }
+                };
+
+                let preview = &mut editor.preview[slot];
+                preview.pipe.set_level_pipe(&entry(i));
+                preview
+                    .pipe
+                    .spawn_blocks(&mut commands, preview.entity, &pipes_handler);
+                preview.shown = Some((i, entry(i)));
+                slot
+            }
+        };
+
+        commands
+            .entity(editor.preview[slot].entity)
+            .insert(Transform::from_xyz(x, 0.0, 0.0))
+            .insert(Visibility { is_visible: true });
+    }
+
+    for preview in editor.preview.iter().filter(|p| p.shown.is_none()) {
+        commands
+            .entity(preview.entity)
+            .insert(Visibility { is_visible: false });
+    }
+}
+
+fn editor_ui_system(
+    mut text_query: Query<(&mut Text, &mut Visibility), With<EditorText>>,
+    editor: Res<LevelEditor>,
+    game_controller: Res<GameController>,
+) {
+    let (mut text, mut visibility) = text_query.single_mut();
+
+    visibility.is_visible = game_controller.mode == GameMode::Editor;
+
+    let level = match &editor.level {
+        Some(level) => level,
+        None => return,
+    };
+
+    if editor.testing {
+        text.sections[0].value = format!("Testing {} - [Tab] back to editor", level.name);
+        return;
+    }
+
+    let selected = match editor
+        .selected
+        .and_then(|i| level.pipes.get(i).map(|pipe| (i, pipe)))
+    {
+        Some((i, pipe)) => format!(
+            "Pipe {}/{} - height {}, gap {}, width {}{}",
+            i + 1,
+            level.pipes.len(),
+            pipe.height,
+            pipe.gap,
+            pipe.width,
+            if pipe.movement.is_some() {
+                ", moving"
+            } else {
+                ""
+            }
+        ),
+        None => format!("{} pipes", level.pipes.len()),
+    };
+
+    text.sections[0].value = format!(
+        "{} - {}\n{}\n[Arrows] scroll [Click] place/drag [Right click] remove [Wheel] height \
+         [Shift] gap [Ctrl] width [M] move [Tab] test [Ctrl+S] save",
+        level.name, editor.status, selected
+    );
+}
+
+fn cursor_world_position(
+    windows: &Windows,
+    camera_query: &Query<(&Camera, &GlobalTransform)>,
+) -> Option<Vec2> {
+    let window = windows.get_primary()?;
+    let cursor = window.cursor_position()?;
+    let (camera, camera_transform) = camera_query.get_single().ok()?;
+
+    // screen -> normalized device -> world coordinates
+    let window_size = Vec2::new(window.width(), window.height());
+    let ndc = (cursor / window_size) * 2.0 - Vec2::ONE;
+    let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix().inverse();
+
+    Some(ndc_to_world.project_point3(ndc.extend(-1.0)).truncate())
+}
+
+/// Index of the pipe under the screen position
+fn pipe_at(level: &Level, scroll: f32, screen_x: f32) -> Option<usize> {
+    (0..level.pipes.len()).rev().find(|i| {
+        let x = level.pipe_x(*i) - scroll + PIPES_START_X;
+        let right = x + level.pipes[*i].width as f32 * 0.5 * SPRITE_SIZE;
+
+        (x - 0.5 * SPRITE_SIZE..right).contains(&screen_x)
+    })
+}
+
+/// Height that centers the gap on `y`
+fn height_at(y: f32, gap: u32) -> u32 {
+    let height = (y / SPRITE_SIZE - PIPE_FLOOR_Y_SPR as f32 - gap as f32 / 2.0).round();
+
+    height.clamp(0.0, PIPE_COLUMN_SPR.saturating_sub(gap) as f32) as u32
+}
+
+fn insert_pipe(level: &mut Level, x: f32, mut pipe: LevelPipe) -> Option<usize> {
+    let i = (0..level.pipes.len())
+        .find(|i| level.pipe_x(*i) > x)
+        .unwrap_or(level.pipes.len());
+
+    let (previous_x, min_spacing) = match i {
+        0 => (0.0, 0.0),
+        _ => (level.pipe_x(i - 1), LEVEL_MIN_PIPE_SPACING),
+    };
+    if x - previous_x < min_spacing {
+        return None;
+    }
+
+    if i < level.pipes.len() {
+        let next_x = level.pipe_x(i);
+        if next_x - x < LEVEL_MIN_PIPE_SPACING {
+            return None;
+        }
+        level.pipes[i].spacing = next_x - x;
+    }
+
+    pipe.spacing = x - previous_x;
+    level.pipes.insert(i, pipe);
+
+    Some(i)
+}
+
+/// Move the pipe without moving the ones after it, returns whether it moved
+fn move_pipe(level: &mut Level, i: usize, x: f32) -> bool {
+    let min_spacing = match i {
+        0 => 0.0,
+        _ => LEVEL_MIN_PIPE_SPACING,
+    };
+
+    let mut delta = (x - level.pipe_x(i)).max(min_spacing - level.pipes[i].spacing);
+    if let Some(next) = level.pipes.get(i + 1) {
+        delta = delta.min(next.spacing - LEVEL_MIN_PIPE_SPACING);
+    }
+
+    if delta == 0.0 {
+        return false;
+    }
+
+    level.pipes[i].spacing += delta;
+    if let Some(next) = level.pipes.get_mut(i + 1) {
+        next.spacing -= delta;
+    }
+
+    true
+}
+
+fn remove_pipe(level: &mut Level, i: usize) {
+    let removed = level.pipes.remove(i);
+
+    // keep the following pipes in place
+    if let Some(next) = level.pipes.get_mut(i) {
+        next.spacing += removed.spacing;
+    }
+}
+
+fn empty_level(level_index: usize) -> Level {
+    Level {
+        name: format!("Level {}", level_index + 1),
+        speed: 1.0,
+        pipes: Vec::new(),
+        two_star_flaps: 0,
+        three_star_flaps: 0,
+        start: 0.0,
+    }
+}
+
+/// Star thresholds following the pipe count, for levels that do not set them
+fn default_star_flaps(level: &mut Level) {
+    let pipes = level.pipes.len() as f32;
+
+    level.two_star_flaps = (pipes * EDITOR_STAR_FLAPS_PER_PIPE.0).ceil() as u32;
+    level.three_star_flaps = (pipes * EDITOR_STAR_FLAPS_PER_PIPE.1).ceil() as u32;
+}
+
+fn save_level(level: &Level, path: &str) -> Result<(), anyhow::Error> {
+    let ron = ron::ser::to_string_pretty(level, ron::ser::PrettyConfig::default())?;
+    std::fs::write(format!("assets/{}", path), ron)?;
+
+    Ok(())
+}
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelEditor>()
+            .add_startup_system(editor_setup)
+            .add_system(editor_mode_system)
+            .add_system(editor_input_system.after(editor_mode_system))
+            .add_system(editor_preview_system.after(editor_input_system))
+            .add_system(editor_ui_system);
+    }
+}
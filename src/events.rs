@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+
+/// The bird flapped
+pub struct Flapped;
+
+/// The bird passed a pipe, `score` includes it
+pub struct PipePassed {
+    pub score: i32,
+}
+
+/// The score just went past the score to beat
+pub struct NewHighScore {
+    pub score: i32,
+}
+
+/// The bird hit a pipe or left the screen
+pub struct PlayerHit;
+
+/// The dead bird fell off the bottom of the screen
+pub struct PlayerLanded;
+
+/// A new run was asked for, from the game over window or by changing the mode
+pub struct RestartRequested;
+
+/// The game was paused behind the settings or the customize window
+pub struct GamePaused;
+
+/// The game went on after a pause
+pub struct GameResumed;
+
+/// The pipes and the bird were reset for a new run
+pub struct GameReset {
+    pub respawned: bool, // at a practice checkpoint instead of the start
+}
+
+pub struct EventsPlugin;
+
+impl Plugin for EventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Flapped>()
+            .add_event::<PipePassed>()
+            .add_event::<NewHighScore>()
+            .add_event::<PlayerHit>()
+            .add_event::<PlayerLanded>()
+            .add_event::<RestartRequested>()
+            .add_event::<GamePaused>()
+            .add_event::<GameResumed>()
+            .add_event::<GameReset>();
+    }
+}
@@ -1,21 +1,72 @@
 use bevy::prelude::*;
+use rand::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 
+use crate::background::sky::SkyMode;
+use crate::events::{GamePaused, GameResumed, PlayerHit, RestartRequested};
+use crate::levels::{Level, LevelPipe};
+use crate::network::protocol::PipeSnapshot;
+use crate::patterns::PatternGenerator;
+use crate::skins::Cosmetics;
+use crate::sound::AudioBus;
+use crate::weather::Weather;
 use crate::*;
 
 pub struct GameController {
     pub game_state: GameState,
     before_pause: GameState,
+    pub mode: GameMode,
     pub speed_multiplier: f32,
+    pub time_scale: f32, // slow motion in practice mode
 
     pub score: i32,
+    pub flaps: u32,
+    pub run_time: f32,
+    pub won: bool,
+    pub coins: u32, // collected this run, they are saved to the statistics right away
+
+    // date of the daily challenge being played, and whether this run is its scored attempt
+    pub daily_date: String,
+    pub daily_attempt: bool,
+
+    pub checkpoint: Option<Checkpoint>,
+
+    // levels mode
+    pub level_index: usize,
+    pub level: Option<Level>,
+    next_level_pipe: usize,
+    pub distance: f32, // how far the pipes moved this run
+    pub stars: u8,
+
+    // seeded pipe generation, shared between clients in versus mode
+    pub seed: u64,
+    pub rng: StdRng,
+    pub patterns: PatternGenerator,
+    pipes_generated: u32,
+    run_checksum: u64, // the pipes and the score they came at, equal for clients in the same race
+    pub checksum_log: Vec<u64>,
+    pub sound_rng: StdRng, // kept apart so sounds never change the pipes
+
     pub player_stats: PlayerStatistics,
     pub settings: GameSettings,
 }
 
 impl GameController {
     pub fn update_highscore(&mut self, mut pkv: ResMut<PkvStore>) {
-        if self.score > self.player_stats.high_score {
+        if self.daily_attempt && self.daily_score() != Some(self.score) {
+            self.player_stats.finish_daily(&self.daily_date, self.score);
+
+            self.save_player_stats(&mut pkv);
+        }
+
+        if self.mode == GameMode::TimeAttack && self.run_time > self.player_stats.time_attack_best {
+            self.player_stats.time_attack_best = self.run_time;
+
+            self.save_player_stats(&mut pkv);
+        }
+
+        if self.mode.records_high_score() && self.score > self.player_stats.high_score {
             self.player_stats.high_score = self.score;
 
             // Save the high score
@@ -23,26 +74,190 @@ impl GameController {
         }
     }
 
+    pub fn start_game(&mut self, pkv: &mut PkvStore) {
+        self.game_state = GameState::Started;
+        self.player_stats.games_played += 1;
+
+        // the first daily run of the day is the scored one, the rest are practice
+        if self.mode == GameMode::Daily && !self.player_stats.played_daily(&self.daily_date) {
+            self.daily_attempt = true;
+            self.player_stats.start_daily(&self.daily_date);
+        }
+
+        self.save_player_stats(pkv);
+    }
+
+    pub fn daily_score(&self) -> Option<i32> {
+        self.player_stats.daily_scores.get(&self.daily_date).copied()
+    }
+
+    pub fn daily_status(&self) -> String {
+        match self.daily_score() {
+            Some(score) => format!("Practice - Today: {}", score),
+            None => format!("Daily Best: {}", self.player_stats.daily_best),
+        }
+    }
+
+    pub fn challenge_status(&self) -> String {
+        match (self.mode, self.player_stats.pipe_rush_best) {
+            (GameMode::PipeRush, Some(best)) => {
+                format!("Pass {} pipes - Best: {:.1}s", PIPE_RUSH_PIPES, best)
+            }
+            (GameMode::PipeRush, None) => format!("Pass {} pipes", PIPE_RUSH_PIPES),
+            _ => format!(
+                "Survive {:.0}s - Best: {:.1}s",
+                TIME_ATTACK_SECS, self.player_stats.time_attack_best
+            ),
+        }
+    }
+
+    /// End a challenge run that reached its goal
+    pub fn win_challenge(&mut self, pkv: &mut PkvStore) {
+        self.game_state = GameState::Finished;
+        self.won = true;
+
+        match self.mode {
+            GameMode::TimeAttack => {
+                self.run_time = TIME_ATTACK_SECS;
+                self.player_stats.time_attack_best = TIME_ATTACK_SECS;
+                self.player_stats.time_attack_wins += 1;
+            }
+            GameMode::Editor => {
+                if let Some(level) = &self.level {
+                    self.stars = level.stars(self.flaps);
+                }
+            }
+            GameMode::Levels => {
+                if let Some(level) = &self.level {
+                    self.stars = level.stars(self.flaps);
+
+                    let best = self
+                        .player_stats
+                        .level_stars
+                        .entry(LEVELS[self.level_index].to_string())
+                        .or_insert(0);
+                    *best = (*best).max(self.stars);
+                }
+            }
+            GameMode::PipeRush => {
+                if self
+                    .player_stats
+                    .pipe_rush_best
+                    .map_or(true, |best| self.run_time < best)
+                {
+                    self.player_stats.pipe_rush_best = Some(self.run_time);
+                }
+            }
+            _ => {}
+        }
+
+        self.save_player_stats(pkv);
+    }
+
+    /// Next pipe of the level and its distance from the level start
+    pub fn next_level_pipe(&mut self) -> Option<(f32, LevelPipe)> {
+        let level = self.level.as_ref()?;
+        let pipe = level.pipes.get(self.next_level_pipe)?.clone();
+        let x = level.pipe_x(self.next_level_pipe);
+
+        self.next_level_pipe += 1;
+        Some((x, pipe))
+    }
+
+    pub fn level_finished(&self) -> bool {
+        self.level.as_ref().map_or(false, |level| {
+            level.finish_x() - self.distance + PIPES_START_X < PLAYER_X
+        })
+    }
+
+    pub fn level_status(&self) -> String {
+        match &self.level {
+            Some(level) => format!(
+                "{}. {} - Best: {}/3",
+                self.level_index + 1,
+                level.name,
+                self.best_level_stars()
+            ),
+            None => "Loading level...".to_string(),
+        }
+    }
+
+    pub fn best_level_stars(&self) -> u8 {
+        self.player_stats
+            .level_stars
+            .get(LEVELS[self.level_index])
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// The score to beat in the current mode
+    pub fn target_score(&self) -> Option<i32> {
+        match self.mode {
+            GameMode::Daily => Some(self.player_stats.daily_best),
+            GameMode::TimeAttack
+            | GameMode::PipeRush
+            | GameMode::Practice
+            | GameMode::Levels
+            | GameMode::Editor => None,
+            _ => Some(self.player_stats.high_score),
+        }
+    }
+
     pub fn reset_game(
         &mut self,
         commands: &mut Commands,
         player: &mut Player,
         player_transform: &mut Transform,
-        pipes_query: &mut Query<&mut PipeParent>,
+        pipes_query: &mut PipesQuery,
         pipes_handler: &PipesHandler,
     ) {
         self.game_state = GameState::Waiting;
 
         self.score = 0;
-        self.speed_multiplier = 1.0;
+        self.flaps = 0;
+        self.run_time = 0.0;
+        self.won = false;
+        self.coins = 0;
+        self.speed_multiplier = match (&self.level, self.mode) {
+            (Some(level), mode) if mode.plays_level() => level.speed,
+            _ => 1.0,
+        };
+        self.checkpoint = None;
+        // test runs from the editor may start partway into the level
+        self.distance = match (&self.level, self.mode) {
+            (Some(level), GameMode::Editor) => level.start,
+            _ => 0.0,
+        };
+        self.next_level_pipe = self
+            .level
+            .as_ref()
+            .map_or(0, |level| level.first_pipe_ahead(self.distance));
+        self.stars = 0;
+        if self.mode != GameMode::Practice {
+            self.time_scale = 1.0;
+        }
         player.die(player_transform);
 
+        self.daily_attempt = false;
+        match self.mode {
+            GameMode::Daily => {
+                self.daily_date = daily::today();
+                self.seed = daily::daily_seed(&self.daily_date);
+            }
+            GameMode::Versus | GameMode::Spectator => {}
+            _ => {
+                self.seed = thread_rng().gen();
+            }
+        }
+        self.reseed();
+
         let pipes_gap_between = 2.0 * SCREEN_X_BOUNDARY / (PIPES_NUMBER as f32);
 
         let mut i = 0.0;
-        for mut pipe in pipes_query.iter_mut() {
+        for (entity, mut pipe, mut transform) in pipes_query.iter_mut() {
             pipe.reset(
                 commands,
+                (entity, &mut *transform),
                 self,
                 pipes_handler,
                 PIPES_START_X + i * pipes_gap_between,
@@ -52,11 +267,104 @@ impl GameController {
         }
     }
 
+    pub fn respawn_at_checkpoint(
+        &mut self,
+        commands: &mut Commands,
+        player: &mut Player,
+        player_transform: &mut Transform,
+        pipes_query: &mut PipesQuery,
+        pipes_handler: &PipesHandler,
+    ) {
+        let checkpoint = match self.checkpoint.clone() {
+            Some(checkpoint) => checkpoint,
+            None => return,
+        };
+
+        self.game_state = GameState::Waiting;
+
+        self.score = checkpoint.score;
+        self.speed_multiplier = checkpoint.speed_multiplier;
+        self.run_time = checkpoint.run_time;
+        self.rng = checkpoint.rng;
+        self.patterns = checkpoint.patterns;
+
+        player.die(player_transform);
+        player.idle_y = checkpoint.player_y;
+        player_transform.translation.y = checkpoint.player_y;
+
+        for (entity, mut pipe, mut transform) in pipes_query.iter_mut() {
+            if let Some(snapshot) = pipe.find_snapshot(&checkpoint.pipes) {
+                pipe.restore(commands, (entity, &mut *transform), pipes_handler, snapshot);
+            }
+        }
+    }
+
+    pub fn delta_time(&self, time: &Time) -> f32 {
+        time.delta_seconds() * self.time_scale
+    }
+
+    pub fn reseed(&mut self) {
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.patterns = PatternGenerator::default();
+        self.pipes_generated = 0;
+        self.run_checksum = self.seed;
+        self.checksum_log.clear();
+        self.sound_rng = StdRng::seed_from_u64(self.seed ^ SOUND_SEED_SALT);
+    }
+
+    pub fn next_pipe_height(&mut self) -> u32 {
+        self.patterns.next_height(&mut self.rng)
+    }
+
+    /// Fold a generated pipe into the run checksum, logged every `CHECKSUM_INTERVAL` pipes.
+    ///
+    /// The birds are left out, each client flies its own.
+    pub fn log_pipe(&mut self, pipe: &PipeSnapshot) {
+        let movement = pipe.movement.map_or([0; 3], |movement| {
+            [movement.amplitude, movement.period, movement.phase].map(f32::to_bits)
+        });
+        let state = [pipe.x.to_bits(), pipe.height, pipe.gap, pipe.width, self.score as u32];
+
+        // FNV-1a steps
+        for value in state.into_iter().chain(movement) {
+            self.run_checksum ^= value as u64;
+            self.run_checksum = self.run_checksum.wrapping_mul(0x100000001b3);
+        }
+        self.pipes_generated += 1;
+
+        if self.pipes_generated % CHECKSUM_INTERVAL == 0 {
+            self.checksum_log.push(self.run_checksum);
+        }
+    }
+
+    pub fn change_mode(&mut self, mode: GameMode, restarts: &mut EventWriter<RestartRequested>) {
+        self.mode = mode;
+
+        // a change made in the settings restarts behind them, the game stays paused
+        restarts.send(RestartRequested);
+    }
+
     pub fn was_game_waiting(&self) -> bool {
         self.game_state == GameState::Waiting
+            || self.is_connecting()
             || (self.game_state == GameState::Paused && self.before_pause == GameState::Waiting)
     }
 
+    pub fn was_game_lost(&self) -> bool {
+        let state = if self.game_state == GameState::Paused {
+            self.before_pause
+        } else {
+            self.game_state
+        };
+
+        state == GameState::Finished && !self.won
+    }
+
+    pub fn is_connecting(&self) -> bool {
+        self.game_state == GameState::Connecting
+            || (self.game_state == GameState::Paused && self.before_pause == GameState::Connecting)
+    }
+
     pub fn is_game_running(&self) -> bool {
         self.game_state == GameState::Started
     }
@@ -72,28 +380,55 @@ impl GameController {
 
     pub fn is_game_finished(&self, player_transform: &Transform) -> bool {
         self.game_state == GameState::Finished
-            && player_transform.translation.y < -SCREEN_Y_BOUNDARY
+            && (self.won || player_transform.translation.y < -SCREEN_Y_BOUNDARY)
     }
 
-    pub fn pause_game(&mut self) {
+    pub fn pause_game(&mut self, pauses: &mut EventWriter<GamePaused>) {
         self.before_pause = self.game_state;
         self.game_state = GameState::Paused;
+
+        pauses.send(GamePaused);
     }
 
-    pub fn resume_game(&mut self) {
+    pub fn resume_game(
+        &mut self,
+        resumes: &mut EventWriter<GameResumed>,
+        restarts: &mut EventWriter<RestartRequested>,
+    ) {
+        self.game_state = self.before_pause;
+        resumes.send(GameResumed);
+
         if self.before_pause == GameState::Finished {
-            self.game_state = GameState::Restart;
-        } else {
-            self.game_state = self.before_pause;
+            restarts.send(RestartRequested);
         }
     }
 
+    pub fn collect_coin(&mut self, pkv: &mut PkvStore) {
+        self.coins += 1;
+        self.player_stats.coins += 1;
+        self.player_stats.coins_collected += 1;
+
+        // kept even if the game is closed in the middle of the run
+        self.save_player_stats(pkv);
+    }
+
     pub fn save_player_stats(&mut self, pkv: &mut PkvStore) {
         pkv.set(PLAYER_STATS_KEY, &self.player_stats)
             .expect("Failed to save high score");
     }
 }
 
+/// Save the records of a run when the bird dies
+pub fn save_run_system(
+    mut hit_events: EventReader<PlayerHit>,
+    mut game_controller: ResMut<GameController>,
+    pkv: ResMut<PkvStore>,
+) {
+    if hit_events.iter().count() > 0 {
+        game_controller.update_highscore(pkv);
+    }
+}
+
 impl FromWorld for GameController {
     fn from_world(world: &mut World) -> Self {
         let pkv = world.get_resource::<PkvStore>().unwrap();
@@ -101,7 +436,7 @@ impl FromWorld for GameController {
         // Load saved data
         let player_stats: PlayerStatistics = pkv
             .get::<PlayerStatistics>(PLAYER_STATS_KEY)
-            .unwrap_or(PlayerStatistics { high_score: 0 });
+            .unwrap_or_default();
 
         let settings: GameSettings =
             pkv.get::<GameSettings>(GAME_SETTINGS_KEY)
@@ -109,10 +444,33 @@ impl FromWorld for GameController {
                     ..Default::default()
                 });
 
+        let seed = thread_rng().gen();
+
         Self {
             game_state: GameState::Waiting,
             before_pause: GameState::Waiting,
+            mode: GameMode::Endless,
+            time_scale: 1.0,
             score: 0,
+            flaps: 0,
+            run_time: 0.0,
+            won: false,
+            coins: 0,
+            daily_date: daily::today(),
+            daily_attempt: false,
+            checkpoint: None,
+            level_index: 0,
+            level: None,
+            next_level_pipe: 0,
+            distance: 0.0,
+            stars: 0,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            patterns: PatternGenerator::default(),
+            pipes_generated: 0,
+            run_checksum: seed,
+            checksum_log: Vec::new(),
+            sound_rng: StdRng::seed_from_u64(seed ^ SOUND_SEED_SALT),
             player_stats,
             settings,
             speed_multiplier: 1.0,
@@ -126,27 +484,232 @@ pub enum GameState {
     Started,
     Paused,
     Finished,
-    Restart,
+    Connecting,
+    Spectating,
+    Editing,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Practice mode save point the player respawns at after dying
+#[derive(Clone)]
+pub struct Checkpoint {
+    pub score: i32,
+    pub speed_multiplier: f32,
+    pub run_time: f32,
+    pub player_y: f32,
+    pub pipes: Vec<PipeSnapshot>,
+    pub rng: StdRng,
+    pub patterns: PatternGenerator,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum GameMode {
+    Endless,
+    Daily,
+    TimeAttack,
+    PipeRush,
+    Practice,
+    Levels,
+    Editor,
+    Versus,
+    Spectator,
+}
+
+impl GameMode {
+    pub fn available() -> Vec<GameMode> {
+        vec![
+            GameMode::Endless,
+            GameMode::Daily,
+            GameMode::TimeAttack,
+            GameMode::PipeRush,
+            GameMode::Practice,
+            GameMode::Levels,
+            #[cfg(not(target_arch = "wasm32"))]
+            GameMode::Editor,
+            #[cfg(not(target_arch = "wasm32"))]
+            GameMode::Versus,
+            #[cfg(not(target_arch = "wasm32"))]
+            GameMode::Spectator,
+        ]
+    }
+
+    pub fn next(&self) -> GameMode {
+        let modes = GameMode::available();
+        let i = modes.iter().position(|mode| mode == self).unwrap_or(0);
+
+        modes[(i + 1) % modes.len()]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Endless => "Endless",
+            GameMode::Daily => "Daily",
+            GameMode::TimeAttack => "Time Attack",
+            GameMode::PipeRush => "Pipe Rush",
+            GameMode::Practice => "Practice",
+            GameMode::Levels => "Levels",
+            GameMode::Editor => "Level Editor",
+            GameMode::Versus => "Versus",
+            GameMode::Spectator => "Spectator",
+        }
+    }
+
+    pub fn records_high_score(&self) -> bool {
+        matches!(self, GameMode::Endless | GameMode::Versus)
+    }
+
+    pub fn is_challenge(&self) -> bool {
+        matches!(self, GameMode::TimeAttack | GameMode::PipeRush)
+    }
+
+    /// Pipes come from `GameController::level` instead of the seeded generator
+    pub fn plays_level(&self) -> bool {
+        matches!(self, GameMode::Levels | GameMode::Editor)
+    }
+
+    /// Every player gets the same seed, so the weather is rolled from it instead of picked
+    pub fn shares_seed(&self) -> bool {
+        matches!(self, GameMode::Daily | GameMode::Versus)
+    }
+
+    /// Weather of the mode until another one is picked in the settings, `None` is random
+    pub fn default_weather(&self) -> Option<Weather> {
+        // levels and challenges are tuned without wind
+        match self.plays_level() || self.is_challenge() {
+            true => Some(Weather::Clear),
+            false => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct PlayerStatistics {
     pub high_score: i32,
+
+    // daily challenge, scores are keyed by date
+    pub daily_scores: BTreeMap<String, i32>,
+    pub daily_best: i32,
+    pub daily_streak: u32,
+
+    // challenge modes, times in seconds
+    pub time_attack_best: f32,
+    pub time_attack_wins: u32,
+    pub pipe_rush_best: Option<f32>,
+
+    // best star rating, keyed by level path
+    pub level_stars: BTreeMap<String, u8>,
+
+    pub coins: u32, // spent on skins in the customize window
+    pub coins_collected: u32,
+    pub cosmetics: Cosmetics,
+
+    pub games_played: u32,
+    pub achievements: BTreeSet<String>,
+}
+
+impl PlayerStatistics {
+    pub fn played_daily(&self, date: &str) -> bool {
+        self.daily_scores.contains_key(date)
+    }
+
+    /// Use up the day's attempt, so quitting mid-run does not grant another one
+    pub fn start_daily(&mut self, date: &str) {
+        let played_yesterday = daily::yesterday(date)
+            .map_or(false, |yesterday| self.played_daily(&yesterday));
+
+        self.daily_streak = if played_yesterday {
+            self.daily_streak + 1
+        } else {
+            1
+        };
+        self.daily_scores.insert(date.to_string(), 0);
+    }
+
+    pub fn finish_daily(&mut self, date: &str, score: i32) {
+        self.daily_scores.insert(date.to_string(), score);
+
+        if score > self.daily_best {
+            self.daily_best = score;
+        }
+    }
+
+    /// Best score and number of days played before `date`
+    pub fn daily_history_before(&self, date: &str) -> (Option<i32>, usize) {
+        let previous = self.daily_scores.range::<str, _>(..date);
+
+        (previous.clone().map(|(_, score)| *score).max(), previous.count())
+    }
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct GameSettings {
+    pub master_vol_level: f64,
     pub music_vol_level: f64,
     pub effects_vol_level: f64,
+    pub ui_vol_level: f64,
+    pub muted: bool,
+    pub sky_mode: SkyMode,
+    pub mode_weather: BTreeMap<String, Option<Weather>>, // keyed by mode name, `None` is random
+    pub theme: usize,             // index in `THEMES`
     pub show_fps: bool,
+    pub broadcast: bool,
 }
 
 impl Default for GameSettings {
     fn default() -> Self {
         GameSettings {
+            master_vol_level: 1.0,
             music_vol_level: 0.5,
             effects_vol_level: 0.5,
+            ui_vol_level: 0.5,
+            muted: false,
+            sky_mode: SkyMode::default(),
+            mode_weather: BTreeMap::new(),
+            theme: 0,
             show_fps: false,
+            broadcast: false,
+        }
+    }
+}
+
+impl GameSettings {
+    pub fn volume_level(&self, bus: AudioBus) -> f64 {
+        match bus {
+            AudioBus::Master => self.master_vol_level,
+            AudioBus::Music => self.music_vol_level,
+            AudioBus::Effects => self.effects_vol_level,
+            AudioBus::Ui => self.ui_vol_level,
+        }
+    }
+
+    pub fn volume_level_mut(&mut self, bus: AudioBus) -> &mut f64 {
+        match bus {
+            AudioBus::Master => &mut self.master_vol_level,
+            AudioBus::Music => &mut self.music_vol_level,
+            AudioBus::Effects => &mut self.effects_vol_level,
+            AudioBus::Ui => &mut self.ui_vol_level,
+        }
+    }
+
+    /// Weather picked for `mode`, `None` rolls it from the seed of every run
+    pub fn weather(&self, mode: GameMode) -> Option<Weather> {
+        self.mode_weather
+            .get(mode.name())
+            .copied()
+            .unwrap_or_else(|| mode.default_weather())
+    }
+
+    pub fn set_weather(&mut self, mode: GameMode, weather: Option<Weather>) {
+        self.mode_weather.insert(mode.name().to_string(), weather);
+    }
+
+    /// Volume the bus plays at, after the master volume and mute
+    pub fn output_volume(&self, bus: AudioBus) -> f64 {
+        match (self.muted, bus) {
+            (true, _) => 0.0,
+            (false, AudioBus::Master) => self.master_vol_level,
+            (false, bus) => self.master_vol_level * self.volume_level(bus),
         }
     }
 }
@@ -0,0 +1,220 @@
+use bevy::asset::{AssetLoader, LoadContext, LoadState, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::events::RestartRequested;
+use crate::*;
+
+/// Hand-authored pipe sequence, loaded from `assets/levels/*.level.ron`
+#[derive(Serialize, Deserialize, TypeUuid, Clone, Debug, PartialEq)]
+#[uuid = "5f3d0a2c-8a8e-4b51-9d55-2f4c3f7a1e61"]
+pub struct Level {
+    pub name: String,
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+    pub pipes: Vec<LevelPipe>,
+    // finishing with at most this many flaps earns two or three stars
+    pub two_star_flaps: u32,
+    pub three_star_flaps: u32,
+    // distance a test run from the editor starts at
+    #[serde(skip)]
+    pub start: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LevelPipe {
+    pub spacing: f32, // distance from the previous pipe, or from the start for the first one
+    pub height: u32,
+    pub gap: u32,
+    #[serde(default = "default_width")]
+    pub width: u32,
+    #[serde(default)]
+    pub movement: Option<LevelPipeMovement>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct LevelPipeMovement {
+    pub amplitude: f32, // in sprites
+    pub period: f32,
+    #[serde(default)]
+    pub phase: f32, // fraction of the period
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+fn default_width() -> u32 {
+    PIPE_WIDTH
+}
+
+impl Level {
+    /// Distance of the pipe from the level start
+    pub fn pipe_x(&self, index: usize) -> f32 {
+        self.pipes
+            .iter()
+            .take(index + 1)
+            .enumerate()
+            .map(|(i, pipe)| match i {
+                0 => pipe.spacing.max(0.0),
+                // closer pipes would have to appear on screen
+                _ => pipe.spacing.max(LEVEL_MIN_PIPE_SPACING),
+            })
+            .sum()
+    }
+
+    /// First pipe still in front of the bird when the run starts at `distance`
+    pub fn first_pipe_ahead(&self, distance: f32) -> usize {
+        (0..self.pipes.len())
+            .find(|i| self.pipe_x(*i) - distance + PIPES_START_X >= PLAYER_X)
+            .unwrap_or(self.pipes.len())
+    }
+
+    pub fn finish_x(&self) -> f32 {
+        match self.pipes.len() {
+            0 => LEVEL_FINISH_SPACING,
+            len => self.pipe_x(len - 1) + LEVEL_FINISH_SPACING,
+        }
+    }
+
+    pub fn stars(&self, flaps: u32) -> u8 {
+        if flaps <= self.three_star_flaps {
+            3
+        } else if flaps <= self.two_star_flaps {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let level: Level = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+pub struct LevelsHandler {
+    pub levels: Vec<Handle<Level>>,
+}
+
+impl FromWorld for LevelsHandler {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
+
+        LevelsHandler {
+            levels: LEVELS.iter().map(|path| asset_server.load(*path)).collect(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct FinishLine;
+
+fn levels_setup(mut commands: Commands) {
+    commands
+        .spawn()
+        .insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::WHITE,
+                custom_size: Some(Vec2::new(SPRITE_SIZE / 4.0, WINDOW_HEIGHT)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, Z_PIPE)),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(FinishLine);
+}
+
+/// Copy the selected level into the game controller whenever it loads or changes on disk
+fn level_sync_system(
+    mut game_controller: ResMut<GameController>,
+    mut level_events: EventReader<AssetEvent<Level>>,
+    mut synced_level: Local<Option<usize>>,
+    mut restart_events: EventWriter<RestartRequested>,
+    levels_handler: Res<LevelsHandler>,
+    levels: Res<Assets<Level>>,
+    asset_server: Res<AssetServer>,
+) {
+    let level_index = game_controller.level_index;
+    let handle = &levels_handler.levels[level_index];
+
+    let modified = level_events.iter().any(|event| match event {
+        AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed } => {
+            changed == handle
+        }
+        AssetEvent::Removed { .. } => false,
+    });
+
+    // the editor manages the level itself, sync again once it is left
+    if game_controller.mode == GameMode::Editor {
+        *synced_level = None;
+        return;
+    }
+
+    if *synced_level == Some(level_index) && !modified {
+        return;
+    }
+
+    if let Some(level) = levels.get(handle) {
+        game_controller.level = Some(level.clone());
+        *synced_level = Some(level_index);
+
+        if game_controller.mode == GameMode::Levels {
+            game_controller.change_mode(GameMode::Levels, &mut restart_events);
+        }
+    } else if asset_server.get_load_state(handle) == LoadState::Failed {
+        warn!("Failed to load level {}", LEVELS[level_index]);
+
+        game_controller.level = None;
+        *synced_level = Some(level_index);
+    }
+}
+
+fn finish_line_system(
+    mut finish_query: Query<(&mut Transform, &mut Visibility), With<FinishLine>>,
+    game_controller: Res<GameController>,
+) {
+    let (mut transform, mut visibility) = finish_query.single_mut();
+
+    match (&game_controller.level, game_controller.mode) {
+        (Some(level), mode) if mode.plays_level() => {
+            visibility.is_visible = true;
+            transform.translation.x = level.finish_x() - game_controller.distance + PIPES_START_X;
+        }
+        _ => {
+            visibility.is_visible = false;
+        }
+    }
+}
+
+pub struct LevelsPlugin;
+
+impl Plugin for LevelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .init_resource::<LevelsHandler>()
+            .add_startup_system(levels_setup)
+            .add_system(level_sync_system)
+            .add_system(finish_line_system);
+    }
+}
@@ -11,13 +11,30 @@ use bevy::{
 // use bevy_framepace;
 use bevy_pkv::PkvStore;
 
+mod achievements;
 mod background;
+mod challenge;
+mod coins;
+mod daily;
+mod difficulty;
+mod editor;
+mod events;
 mod game_controller;
+mod levels;
+mod music;
+mod network;
 mod options;
+mod particles;
+mod patterns;
 mod pipes;
 mod player;
+mod powerups;
+mod practice;
+mod skins;
 mod sound;
+mod themes;
 mod ui;
+mod weather;
 mod window;
 
 use background::BackgroundPlugin;
@@ -47,14 +64,30 @@ fn main() {
         .insert_resource(PkvStore::new("bewuwy", GAME_NAME))
         // Audio
         .add_plugin(sound::SoundPlugin)
+        .add_plugin(music::MusicPlugin)
         // FPS
         .add_plugin(FrameTimeDiagnosticsPlugin::default())
         // Game systems
+        .add_plugin(events::EventsPlugin)
         .init_resource::<GameController>()
         .add_startup_system(setup)
+        .add_system(save_run_system)
         .add_plugin(PipesPlugin)
         .add_plugin(PlayerPlugin)
+        .add_plugin(themes::ThemesPlugin)
         .add_plugin(BackgroundPlugin)
+        .add_plugin(weather::WeatherPlugin)
+        .add_plugin(coins::CoinsPlugin)
+        .add_plugin(particles::ParticlesPlugin)
+        .add_plugin(powerups::PowerUpsPlugin)
+        .add_plugin(skins::SkinsPlugin)
+        .add_plugin(achievements::AchievementsPlugin)
+        .add_plugin(levels::LevelsPlugin)
+        .add_plugin(editor::EditorPlugin)
+        .add_plugin(challenge::ChallengePlugin)
+        .add_plugin(practice::PracticePlugin)
+        // Versus mode
+        .add_plugin(network::NetworkPlugin)
         // UI
         .add_plugin(ui::UIPlugin)
         // Window
@@ -0,0 +1,338 @@
+use bevy::asset::{AssetLoader, LoadContext, LoadState, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use bevy_kira_audio::prelude::*;
+use rand::prelude::*;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crate::events::{GamePaused, GameResumed, NewHighScore};
+use crate::sound::{AudioBus, MusicChannel};
+use crate::*;
+
+/// Tracks for the menu and for the runs, loaded from `assets/music/*.music.ron`
+#[derive(Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "5c2f7e14-8a3b-4d69-b0e5-72d1c9a4f318"]
+pub struct MusicList {
+    pub menu: Vec<MusicTrack>,
+    pub gameplay: Vec<MusicTrack>,
+    #[serde(default)]
+    pub intensity: MusicIntensity,
+    #[serde(default)]
+    pub stinger: Option<String>, // played over the music when the score to beat is passed
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct MusicTrack {
+    pub name: String,
+    pub path: String,
+}
+
+/// How the gameplay music speeds up with the pipes
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct MusicIntensity {
+    pub speeds: (f32, f32), // speed multipliers the playback rates are reached at
+    pub playback_rates: (f64, f64), // of the track, in between they are interpolated
+}
+
+impl Default for MusicIntensity {
+    fn default() -> Self {
+        MusicIntensity {
+            speeds: (1.0, 2.0),
+            playback_rates: (1.0, 1.0),
+        }
+    }
+}
+
+impl MusicIntensity {
+    pub fn playback_rate(&self, speed_multiplier: f32) -> f64 {
+        let (slow, fast) = self.speeds;
+        let t = ((speed_multiplier - slow) / (fast - slow)).clamp(0.0, 1.0) as f64;
+
+        let (slow_rate, fast_rate) = self.playback_rates;
+        slow_rate + (fast_rate - slow_rate) * t
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MusicMood {
+    Menu,
+    Gameplay,
+}
+
+impl MusicList {
+    pub fn tracks(&self, mood: MusicMood) -> &[MusicTrack] {
+        match mood {
+            MusicMood::Menu => &self.menu,
+            MusicMood::Gameplay => &self.gameplay,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MusicListLoader;
+
+impl AssetLoader for MusicListLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let list: MusicList = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(list));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["music.ron"]
+    }
+}
+
+pub struct MusicHandler {
+    pub list: Handle<MusicList>,
+}
+
+impl FromWorld for MusicHandler {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
+
+        MusicHandler {
+            list: asset_server.load(MUSIC_LIST_PATH),
+        }
+    }
+}
+
+/// The track that is playing and the ones coming up
+#[derive(Default)]
+pub struct Music {
+    mood: Option<MusicMood>,
+    playing: Option<Handle<AudioInstance>>,
+    next: Option<(MusicTrack, Handle<AudioSource>)>, // waiting for it to load
+    playlist: Vec<usize>,                            // shuffled track indices of the mood
+    failed: HashSet<String>, // tracks that could not be loaded, only reported once
+    list_failed: bool,
+    volume: f64,
+    ducked: bool, // behind the settings or the customize window
+    playback_rate: f64,
+    stinger: Option<Handle<AudioSource>>,
+    stinger_failed: bool,
+}
+
+impl Music {
+    fn has_finished(&self, instances: &Assets<AudioInstance>) -> bool {
+        match &self.playing {
+            // the instance only shows up once the audio plugin started it
+            Some(playing) => instances.get(playing).map_or(false, |instance| {
+                matches!(instance.state(), PlaybackState::Stopped)
+            }),
+            None => true,
+        }
+    }
+
+    fn stop(&mut self, instances: &mut Assets<AudioInstance>, tween: AudioTween) {
+        if let Some(instance) = self
+            .playing
+            .take()
+            .and_then(|playing| instances.get_mut(&playing))
+        {
+            instance.stop(tween);
+        }
+    }
+}
+
+fn music_system(
+    mut music: ResMut<Music>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    (mut pause_events, mut resume_events): (EventReader<GamePaused>, EventReader<GameResumed>),
+    music_channel: Res<AudioChannel<MusicChannel>>,
+    asset_server: Res<AssetServer>,
+    (music_handler, lists): (Res<MusicHandler>, Res<Assets<MusicList>>),
+    game_controller: Res<GameController>,
+) {
+    // duck when the game is paused or resumed, the state says which came last
+    if pause_events.iter().count() + resume_events.iter().count() > 0 {
+        music.ducked = game_controller.is_game_paused();
+    }
+
+    let list = match lists.get(&music_handler.list) {
+        Some(list) => list,
+        None => {
+            if asset_server.get_load_state(&music_handler.list) == LoadState::Failed
+                && !music.list_failed
+            {
+                warn!("Could not load the music list {}", MUSIC_LIST_PATH);
+                music.list_failed = true;
+            }
+            return;
+        }
+    };
+
+    // duck while paused and fade down once the bird died
+    let mut level = 1.0;
+    let mut duck_secs = MUSIC_DUCK_SECS;
+    if game_controller.was_game_lost() {
+        level = MUSIC_LOST_LEVEL;
+        duck_secs = MUSIC_LOST_FADE_SECS;
+    }
+    if music.ducked {
+        level = level.min(MUSIC_PAUSED_LEVEL);
+        duck_secs = MUSIC_DUCK_SECS;
+    }
+
+    // follow the volume setting
+    let volume = game_controller.settings.output_volume(AudioBus::Music) * level;
+    if volume != music.volume {
+        music.volume = volume;
+
+        if let Some(instance) = music.playing.as_ref().and_then(|h| instances.get_mut(h)) {
+            instance.set_volume(
+                volume,
+                AudioTween::new(Duration::from_secs_f32(duck_secs), AudioEasing::OutPowi(2)),
+            );
+        }
+    }
+
+    let mood = if game_controller.was_game_waiting() {
+        MusicMood::Menu
+    } else {
+        MusicMood::Gameplay
+    };
+
+    // speed the gameplay music up along with the pipes
+    let playback_rate = match mood {
+        MusicMood::Menu => 1.0,
+        MusicMood::Gameplay => list
+            .intensity
+            .playback_rate(game_controller.speed_multiplier),
+    };
+    if (playback_rate - music.playback_rate).abs() > 0.001 {
+        music.playback_rate = playback_rate;
+
+        if let Some(instance) = music.playing.as_ref().and_then(|h| instances.get_mut(h)) {
+            instance.set_playback_rate(
+                playback_rate,
+                AudioTween::new(Duration::from_secs(1), AudioEasing::Linear),
+            );
+        }
+    }
+
+    if music.stinger.is_none() {
+        music.stinger = list.stinger.as_ref().map(|path| asset_server.load(path));
+    }
+
+    if music.mood != Some(mood) {
+        music.mood = Some(mood);
+        music.playlist.clear();
+        music.next = None;
+    } else if music.next.is_none() && !music.has_finished(&instances) {
+        return;
+    }
+
+    let fade = AudioTween::new(
+        Duration::from_secs_f32(MUSIC_CROSSFADE_SECS),
+        AudioEasing::OutPowi(2),
+    );
+
+    // pick the next track, reshuffling once the playlist runs out
+    if music.next.is_none() {
+        let tracks = list.tracks(mood);
+
+        if music.playlist.is_empty() {
+            let failed = &music.failed;
+            let mut playlist: Vec<usize> = (0..tracks.len())
+                .filter(|i| !failed.contains(&tracks[*i].path))
+                .collect();
+            playlist.shuffle(&mut thread_rng());
+            music.playlist = playlist;
+        }
+
+        let track = match music.playlist.pop() {
+            Some(index) => tracks[index].clone(),
+            None => {
+                // nothing left to play for this mood
+                music.stop(&mut instances, fade);
+                return;
+            }
+        };
+        let source = asset_server.load(&track.path);
+        music.next = Some((track, source));
+    }
+
+    let (track, source) = music.next.clone().unwrap();
+    match asset_server.get_load_state(&source) {
+        LoadState::Loaded => {}
+        LoadState::Failed => {
+            warn!(
+                "Could not load the music track {} from {}",
+                track.name, track.path
+            );
+            music.failed.insert(track.path);
+            music.next = None;
+
+            // pick again from the tracks that are left
+            music.mood = None;
+            return;
+        }
+        // the old track keeps playing until the new one is ready
+        _ => return,
+    }
+    music.next = None;
+
+    // crossfade into the new track
+    music.stop(&mut instances, fade.clone());
+    info!("Playing music: {}", track.name);
+    music.playing = Some(
+        music_channel
+            .play(source)
+            .with_volume(volume)
+            .with_playback_rate(playback_rate)
+            .fade_in(fade)
+            .handle(),
+    );
+}
+
+fn stinger_system(
+    mut high_score_events: EventReader<NewHighScore>,
+    mut music: ResMut<Music>,
+    music_channel: Res<AudioChannel<MusicChannel>>,
+    asset_server: Res<AssetServer>,
+    game_controller: Res<GameController>,
+) {
+    if high_score_events.iter().count() == 0 {
+        return;
+    }
+
+    let stinger = match music.stinger.clone() {
+        Some(stinger) => stinger,
+        None => return,
+    };
+    match asset_server.get_load_state(&stinger) {
+        LoadState::Loaded => {
+            music_channel
+                .play(stinger)
+                .with_volume(game_controller.settings.output_volume(AudioBus::Music));
+        }
+        LoadState::Failed if !music.stinger_failed => {
+            warn!("Could not load the music stinger");
+            music.stinger_failed = true;
+        }
+        _ => {}
+    }
+}
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<MusicList>()
+            .init_asset_loader::<MusicListLoader>()
+            .init_resource::<MusicHandler>()
+            .init_resource::<Music>()
+            .add_system(music_system)
+            .add_system(stinger_system);
+    }
+}
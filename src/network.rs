@@ -0,0 +1,382 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::events::RestartRequested;
+use crate::ui::{HudText, UiZ};
+use crate::*;
+
+mod connection;
+pub mod protocol;
+mod spectator;
+
+use connection::LineConnection;
+use protocol::NetMessage;
+
+static CONNECT_RETRY_SECS: f64 = 2.0;
+static GHOST_ALPHA: f32 = 0.4;
+
+#[derive(Default)]
+pub struct VersusSession {
+    connection: Option<LineConnection>,
+    last_attempt: Option<f64>,
+
+    pub matched: bool,
+    pub opponent_score: i32,
+    pub opponent_dead: bool,
+    opponent_checksums: HashMap<u32, u64>,
+    pub desync: bool,
+
+    // local progress already sent to the opponent
+    sent_flaps: u32,
+    sent_score: i32,
+    sent_checksums: usize,
+    sent_death: bool,
+}
+
+impl VersusSession {
+    fn send(&mut self, message: NetMessage) {
+        if let Some(connection) = self.connection.as_mut() {
+            connection.send(&message);
+        }
+    }
+
+    fn reset_opponent(&mut self) {
+        self.opponent_score = 0;
+        self.opponent_dead = false;
+        self.opponent_checksums.clear();
+        self.desync = false;
+    }
+}
+
+#[derive(Component)]
+pub struct Ghost {
+    delta_y: f32,
+    active: bool,
+    dead: bool,
+}
+
+impl Ghost {
+    fn reset(&mut self, transform: &mut Transform) {
+        self.delta_y = 0.0;
+        self.active = false;
+        self.dead = false;
+
+        transform.translation.y = PLAYER_START_Y;
+        transform.rotation = Quat::IDENTITY;
+    }
+}
+
+#[derive(Component)]
+struct VersusText;
+
+fn versus_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    player_handler: Res<PlayerHandler>,
+) {
+    // opponent ghost bird
+    commands
+        .spawn()
+        .insert_bundle(SpriteBundle {
+            texture: player_handler.texture.clone(),
+            transform: Transform::from_translation(Vec3::new(
+                PLAYER_X,
+                PLAYER_START_Y,
+                Z_PLAYER - 1.0,
+            )),
+            sprite: Sprite {
+                color: Color::rgba(1.0, 1.0, 1.0, GHOST_ALPHA),
+                ..Default::default()
+            },
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(Ghost {
+            delta_y: 0.0,
+            active: false,
+            dead: false,
+        });
+
+    // live score comparison
+    commands
+        .spawn_bundle(
+            TextBundle::from_sections([
+                TextSection::new(
+                    "",
+                    TextStyle {
+                        font: asset_server.load(FONT_PATH),
+                        font_size: 30.0,
+                        color: Color::BLACK,
+                    },
+                ),
+                TextSection::from_style(TextStyle {
+                    font: asset_server.load(FONT_PATH),
+                    font_size: 30.0,
+                    color: Color::RED,
+                }),
+            ])
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(5.0),
+                    right: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..default()
+            }),
+        )
+        .insert(VersusText)
+        .insert(HudText)
+        .insert(UiZ(20.0));
+}
+
+fn versus_connection_system(
+    mut session: ResMut<VersusSession>,
+    mut game_controller: ResMut<GameController>,
+    time: Res<Time>,
+) {
+    if game_controller.mode != GameMode::Versus {
+        if session.connection.is_some() {
+            info!("Leaving versus mode");
+            *session = VersusSession::default();
+        }
+        return;
+    }
+
+    if session.connection.as_ref().map_or(false, |c| c.closed) {
+        warn!("Lost connection to the relay server");
+        session.connection = None;
+        session.matched = false;
+    }
+
+    if session.connection.is_none() {
+        let now = time.seconds_since_startup();
+        let should_retry = session
+            .last_attempt
+            .map_or(true, |last| now - last > CONNECT_RETRY_SECS);
+
+        if should_retry {
+            session.last_attempt = Some(now);
+
+            match LineConnection::connect(RELAY_ADDRESS) {
+                Ok(connection) => {
+                    info!("Connected to relay server at {}", RELAY_ADDRESS);
+                    session.connection = Some(connection);
+                    session.send(NetMessage::Join);
+                }
+                Err(e) => {
+                    warn!("Failed to connect to relay server at {}: {}", RELAY_ADDRESS, e);
+                }
+            }
+        }
+    }
+
+    // only allow starting once an opponent has been found
+    if session.matched && game_controller.game_state == GameState::Connecting {
+        game_controller.game_state = GameState::Waiting;
+    } else if !session.matched && game_controller.game_state == GameState::Waiting {
+        game_controller.game_state = GameState::Connecting;
+    }
+}
+
+fn versus_receive_system(
+    mut session: ResMut<VersusSession>,
+    mut game_controller: ResMut<GameController>,
+    mut ghost_query: Query<(&mut Ghost, &mut Transform)>,
+    mut restart_events: EventWriter<RestartRequested>,
+) {
+    let messages = match session.connection.as_mut() {
+        Some(connection) => connection.receive(),
+        None => return,
+    };
+
+    let (mut ghost, mut ghost_transform) = ghost_query.single_mut();
+
+    for message in messages {
+        match message {
+            NetMessage::Start { seed } => {
+                info!("Opponent found, starting race with seed {}", seed);
+
+                session.matched = true;
+                session.reset_opponent();
+                ghost.reset(&mut ghost_transform);
+
+                game_controller.seed = seed;
+                game_controller.change_mode(GameMode::Versus, &mut restart_events);
+            }
+            NetMessage::OpponentLeft => {
+                info!("Opponent left, waiting for a new one");
+
+                session.matched = false;
+                session.reset_opponent();
+                ghost.reset(&mut ghost_transform);
+
+                session.send(NetMessage::Join);
+            }
+            NetMessage::Flap => {
+                if !ghost.dead {
+                    ghost.active = true;
+                    ghost.delta_y = JUMP_FORCE;
+                }
+            }
+            NetMessage::Score { score } => {
+                session.opponent_score = score;
+            }
+            NetMessage::Death { score } => {
+                session.opponent_score = score;
+                session.opponent_dead = true;
+                ghost.dead = true;
+            }
+            NetMessage::Reset => {
+                session.reset_opponent();
+                ghost.reset(&mut ghost_transform);
+            }
+            NetMessage::Checksum { index, value } => {
+                session.opponent_checksums.insert(index, value);
+            }
+            NetMessage::Join => {}
+        }
+    }
+
+    // compare the simulation checksums both sides have reached
+    if !session.desync {
+        let mismatch = session.opponent_checksums.iter().any(|(index, value)| {
+            game_controller
+                .checksum_log
+                .get(*index as usize)
+                .map_or(false, |local| local != value)
+        });
+
+        if mismatch {
+            warn!("Desync detected, the pipes or scores differ from the opponent");
+            session.desync = true;
+        }
+    }
+}
+
+fn versus_send_system(
+    mut session: ResMut<VersusSession>,
+    game_controller: Res<GameController>,
+    player_query: Query<&Player>,
+) {
+    if !session.matched {
+        return;
+    }
+
+    let player = player_query.single();
+
+    // game was reset
+    if game_controller.flaps < session.sent_flaps
+        || (session.sent_death && !player.dead && game_controller.flaps == 0)
+    {
+        session.sent_flaps = 0;
+        session.sent_score = 0;
+        session.sent_death = false;
+        session.send(NetMessage::Reset);
+    }
+
+    while session.sent_flaps < game_controller.flaps {
+        session.sent_flaps += 1;
+        session.send(NetMessage::Flap);
+    }
+
+    if session.sent_score != game_controller.score {
+        session.sent_score = game_controller.score;
+        session.send(NetMessage::Score {
+            score: game_controller.score,
+        });
+    }
+
+    if player.dead && !session.sent_death {
+        session.sent_death = true;
+        session.send(NetMessage::Death {
+            score: game_controller.score,
+        });
+    }
+
+    // checksums are cleared whenever the pipes are reseeded
+    if game_controller.checksum_log.len() < session.sent_checksums {
+        session.sent_checksums = game_controller.checksum_log.len();
+    }
+    while session.sent_checksums < game_controller.checksum_log.len() {
+        let index = session.sent_checksums;
+        session.send(NetMessage::Checksum {
+            index: index as u32,
+            value: game_controller.checksum_log[index],
+        });
+        session.sent_checksums += 1;
+    }
+}
+
+fn ghost_system(
+    mut ghost_query: Query<(&mut Ghost, &mut Transform, &mut Visibility)>,
+    session: Res<VersusSession>,
+    game_controller: Res<GameController>,
+    time: Res<Time>,
+) {
+    const MIN_ROTATION: f32 = -0.4;
+    const ROTATION_SPEED: f32 = 3.0;
+
+    let delta_time: f32 = time.delta().as_secs_f32();
+    let (mut ghost, mut transform, mut visibility) = ghost_query.single_mut();
+
+    visibility.is_visible = game_controller.mode == GameMode::Versus
+        && session.matched
+        && transform.translation.y > -SCREEN_Y_BOUNDARY;
+
+    if !ghost.active {
+        return;
+    }
+
+    ghost.delta_y -= GRAVITY * delta_time;
+    if ghost.dead {
+        ghost.delta_y -= GRAVITY * delta_time;
+    }
+    transform.translation.y += ghost.delta_y;
+
+    if transform.rotation.z > MIN_ROTATION {
+        transform.rotate_z(-ROTATION_SPEED * delta_time);
+    }
+
+    // stop simulating once the ghost fell off screen
+    if transform.translation.y < -SCREEN_Y_BOUNDARY {
+        ghost.active = false;
+    }
+}
+
+fn versus_ui_system(
+    mut text_query: Query<(&mut Text, &mut Visibility), With<VersusText>>,
+    session: Res<VersusSession>,
+    game_controller: Res<GameController>,
+) {
+    let (mut text, mut visibility) = text_query.single_mut();
+
+    visibility.is_visible = game_controller.mode == GameMode::Versus && session.matched;
+
+    text.sections[0].value = format!(
+        "You {} - {} Rival{}",
+        game_controller.score,
+        session.opponent_score,
+        if session.opponent_dead { " (dead)" } else { "" }
+    );
+    text.sections[1].value = if session.desync {
+        " Desync!".to_string()
+    } else {
+        "".to_string()
+    };
+}
+
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VersusSession>()
+            .add_startup_system(versus_setup)
+            .add_system(versus_connection_system)
+            .add_system(versus_receive_system)
+            .add_system(versus_send_system)
+            .add_system(ghost_system)
+            .add_system(versus_ui_system)
+            .add_plugin(spectator::SpectatorPlugin);
+    }
+}
@@ -0,0 +1,97 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use super::protocol::NetMessage;
+
+// drop peers that stop reading instead of buffering forever
+static MAX_WRITE_BUFFER: usize = 1024 * 1024;
+// games connect from a frame system, an unreachable host must not freeze the window
+static CONNECT_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Non-blocking TCP stream that exchanges newline separated messages.
+pub struct LineConnection {
+    stream: TcpStream,
+    read_buffer: Vec<u8>,
+    write_buffer: Vec<u8>,
+    pub closed: bool,
+}
+
+impl LineConnection {
+    pub fn new(stream: TcpStream) -> std::io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self {
+            stream,
+            read_buffer: Vec::new(),
+            write_buffer: Vec::new(),
+            closed: false,
+        })
+    }
+
+    /// Connect to `address`, giving up after `CONNECT_TIMEOUT`
+    pub fn connect(address: &str) -> std::io::Result<Self> {
+        let socket_address = address.to_socket_addrs()?.next().ok_or_else(|| {
+            std::io::Error::new(ErrorKind::InvalidInput, "address did not resolve")
+        })?;
+
+        let stream = TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT)?;
+        Self::new(stream)
+    }
+
+    pub fn send(&mut self, message: &NetMessage) {
+        self.send_line(&message.to_line());
+    }
+
+    pub fn send_line(&mut self, line: &str) {
+        self.write_buffer.extend_from_slice(line.as_bytes());
+        self.flush();
+
+        if self.write_buffer.len() > MAX_WRITE_BUFFER {
+            self.closed = true;
+        }
+    }
+
+    pub fn flush(&mut self) {
+        while !self.write_buffer.is_empty() && !self.closed {
+            match self.stream.write(&self.write_buffer) {
+                Ok(0) => self.closed = true,
+                Ok(written) => {
+                    self.write_buffer.drain(..written);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => self.closed = true,
+            }
+        }
+    }
+
+    /// Returns every complete line received since the last call
+    pub fn receive_lines(&mut self) -> Vec<String> {
+        let mut chunk = [0u8; 1024];
+
+        while !self.closed {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => self.closed = true,
+                Ok(read) => self.read_buffer.extend_from_slice(&chunk[..read]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => self.closed = true,
+            }
+        }
+
+        let mut lines = Vec::new();
+        while let Some(end) = self.read_buffer.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.read_buffer.drain(..=end).collect();
+            lines.push(String::from_utf8_lossy(&line).trim().to_string());
+        }
+
+        lines
+    }
+
+    pub fn receive(&mut self) -> Vec<NetMessage> {
+        self.receive_lines()
+            .iter()
+            .filter_map(|line| NetMessage::from_line(line))
+            .collect()
+    }
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+/// Messages exchanged between the game clients and the relay server,
+/// sent as one JSON object per line.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NetMessage {
+    // client -> relay
+    Join,
+
+    // relay -> client
+    Start { seed: u64 },
+    OpponentLeft,
+
+    // client -> client (forwarded by the relay)
+    Flap,
+    Score { score: i32 },
+    Death { score: i32 },
+    Reset,
+    Checksum { index: u32, value: u64 },
+}
+
+impl NetMessage {
+    pub fn to_line(&self) -> String {
+        let mut line = serde_json::to_string(self).expect("Failed to serialize message");
+        line.push('\n');
+        line
+    }
+
+    pub fn from_line(line: &str) -> Option<NetMessage> {
+        serde_json::from_str(line.trim()).ok()
+    }
+}
+
+/// Live simulation state published by a broadcasting game, one per line.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GameSnapshot {
+    pub score: i32,
+    pub high_score: i32,
+    pub bird_y: f32,
+    pub bird_rotation: f32,
+    pub pipes: Vec<PipeSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PipeSnapshot {
+    #[serde(default)]
+    pub index: Option<usize>, // missing from older broadcasters, which send the pipes in order
+    pub x: f32,
+    pub height: u32,
+    pub gap: u32,
+    #[serde(default = "default_pipe_width")]
+    pub width: u32,
+    #[serde(default)]
+    pub passed: bool,
+    #[serde(default)]
+    pub y_offset: f32,
+    #[serde(default)]
+    pub movement: Option<PipeMovement>,
+}
+
+// `PIPE_WIDTH`, spelled out as the relay server shares this file without the game options
+fn default_pipe_width() -> u32 {
+    2
+}
+
+/// Vertical oscillation of a pipe gap, in pixels and seconds
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PipeMovement {
+    pub amplitude: f32,
+    pub period: f32,
+    pub phase: f32,
+}
+
+impl GameSnapshot {
+    pub fn to_line(&self) -> String {
+        let mut line = serde_json::to_string(self).expect("Failed to serialize snapshot");
+        line.push('\n');
+        line
+    }
+
+    pub fn from_line(line: &str) -> Option<GameSnapshot> {
+        serde_json::from_str(line.trim()).ok()
+    }
+}
@@ -0,0 +1,196 @@
+use bevy::math::EulerRot;
+use bevy::prelude::*;
+use std::net::TcpListener;
+
+use super::connection::LineConnection;
+use super::protocol::GameSnapshot;
+use crate::*;
+
+static CONNECT_RETRY_SECS: f64 = 2.0;
+
+/// Publishes the live game to every connected spectator
+#[derive(Default)]
+struct Broadcaster {
+    listener: Option<TcpListener>,
+    spectators: Vec<LineConnection>,
+    failed: bool,
+}
+
+/// Connection to a broadcasting game while in spectator mode
+#[derive(Default)]
+struct SpectatorFeed {
+    connection: Option<LineConnection>,
+    last_attempt: Option<f64>,
+}
+
+fn broadcast_system(
+    mut broadcaster: ResMut<Broadcaster>,
+    game_controller: Res<GameController>,
+    player_query: Query<(&Player, &Transform)>,
+    pipes_query: Query<(&PipeParent, &Transform)>,
+) {
+    let broadcasting =
+        game_controller.settings.broadcast && game_controller.mode != GameMode::Spectator;
+
+    if !broadcasting {
+        if broadcaster.listener.is_some() {
+            info!("Stopped broadcasting");
+            *broadcaster = Broadcaster::default();
+        }
+        broadcaster.failed = false;
+        return;
+    }
+
+    if broadcaster.listener.is_none() && !broadcaster.failed {
+        match TcpListener::bind(BROADCAST_ADDRESS) {
+            Ok(listener) => {
+                if let Err(e) = listener.set_nonblocking(true) {
+                    warn!("Failed to set broadcast listener to non-blocking: {}", e);
+                    broadcaster.failed = true;
+                    return;
+                }
+                info!("Broadcasting game on {}", BROADCAST_ADDRESS);
+                broadcaster.listener = Some(listener);
+            }
+            Err(e) => {
+                warn!("Failed to broadcast on {}: {}", BROADCAST_ADDRESS, e);
+                broadcaster.failed = true;
+                return;
+            }
+        }
+    }
+
+    // accept new spectators
+    let mut new_spectators = Vec::new();
+    if let Some(listener) = broadcaster.listener.as_ref() {
+        while let Ok((stream, peer)) = listener.accept() {
+            match LineConnection::new(stream) {
+                Ok(connection) => {
+                    info!("Spectator connected from {}", peer);
+                    new_spectators.push(connection);
+                }
+                Err(e) => warn!("Failed to set up spectator {}: {}", peer, e),
+            }
+        }
+    }
+    broadcaster.spectators.extend(new_spectators);
+
+    if broadcaster.spectators.is_empty() {
+        return;
+    }
+
+    let (_, player_transform) = player_query.single();
+    let (_, _, bird_rotation) = player_transform.rotation.to_euler(EulerRot::XYZ);
+
+    let snapshot = GameSnapshot {
+        score: game_controller.score,
+        high_score: game_controller.player_stats.high_score,
+        bird_y: player_transform.translation.y,
+        bird_rotation,
+        pipes: pipes_query
+            .iter()
+            .map(|(pipe, transform)| pipe.snapshot(transform))
+            .collect(),
+    };
+    let line = snapshot.to_line();
+
+    for spectator in broadcaster.spectators.iter_mut() {
+        spectator.send_line(&line);
+    }
+    broadcaster.spectators.retain(|spectator| !spectator.closed);
+}
+
+fn spectator_system(
+    mut commands: Commands,
+    mut feed: ResMut<SpectatorFeed>,
+    mut game_controller: ResMut<GameController>,
+
+    mut player_query: Query<&mut Transform, With<Player>>,
+    mut pipes_query: PipesQuery,
+
+    pipes_handler: Res<PipesHandler>,
+    time: Res<Time>,
+) {
+    if game_controller.mode != GameMode::Spectator {
+        if feed.connection.is_some() {
+            info!("Stopped spectating");
+            *feed = SpectatorFeed::default();
+        }
+        return;
+    }
+
+    if game_controller.game_state == GameState::Waiting {
+        game_controller.game_state = GameState::Spectating;
+    }
+
+    if feed.connection.as_ref().map_or(false, |c| c.closed) {
+        warn!("Lost connection to the broadcasting game");
+        feed.connection = None;
+    }
+
+    if feed.connection.is_none() {
+        let now = time.seconds_since_startup();
+        if feed
+            .last_attempt
+            .map_or(false, |last| now - last < CONNECT_RETRY_SECS)
+        {
+            return;
+        }
+        feed.last_attempt = Some(now);
+
+        let address =
+            std::env::var("SPECTATE_ADDRESS").unwrap_or_else(|_| SPECTATE_ADDRESS.to_string());
+        match LineConnection::connect(&address) {
+            Ok(connection) => {
+                info!("Spectating game at {}", address);
+                feed.connection = Some(connection);
+            }
+            Err(e) => {
+                warn!("Failed to connect to game at {}: {}", address, e);
+                return;
+            }
+        }
+    }
+
+    // only the most recent state matters
+    let snapshot = feed
+        .connection
+        .as_mut()
+        .unwrap()
+        .receive_lines()
+        .iter()
+        .rev()
+        .find_map(|line| GameSnapshot::from_line(line));
+
+    if let Some(snapshot) = snapshot {
+        game_controller.score = snapshot.score;
+
+        let mut player_transform = player_query.single_mut();
+        player_transform.translation.y = snapshot.bird_y;
+        player_transform.rotation = Quat::from_rotation_z(snapshot.bird_rotation);
+
+        for (entity, mut pipe, mut transform) in pipes_query.iter_mut() {
+            let pipe_snapshot = match pipe.find_snapshot(&snapshot.pipes) {
+                Some(pipe_snapshot) => pipe_snapshot,
+                None => continue,
+            };
+            pipe.apply_snapshot(
+                &mut commands,
+                (entity, &mut *transform),
+                &pipes_handler,
+                pipe_snapshot,
+            );
+        }
+    }
+}
+
+pub struct SpectatorPlugin;
+
+impl Plugin for SpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Broadcaster>()
+            .init_resource::<SpectatorFeed>()
+            .add_system(broadcast_system)
+            .add_system(spectator_system);
+    }
+}
@@ -1,3 +1,6 @@
+use crate::background::sky::SkyPalette;
+use crate::difficulty::DifficultyProfile;
+
 pub static GAME_NAME: &str = "Flappy Bevy";
 pub static WINDOW_WIDTH: f32 = 1280.0;
 pub static WINDOW_HEIGHT: f32 = 720.0;
@@ -8,7 +11,8 @@ pub static BACKGROUND_COLOR: [f32; 3] = [87.0, 169.0, 241.0]; // 124.0, 182.0, 2
 // Z-index
 pub static Z_PLAYER: f32 = 10.0;
 pub static Z_PIPE: f32 = 5.0;
-pub static Z_BACKGROUND: f32 = 2.0;
+pub static Z_WEATHER: f32 = 4.0;
+pub static Z_STARS: f32 = 1.0;
 // pub static Z_SETTINGS: f32 = 30.0;
 // pub static Z_UI: f32 = 20.0;
 
@@ -29,20 +33,142 @@ pub static PIPES_NUMBER: u32 = 5;
 // pub static PIPES_GAP_BETWEEN: f32 = 350.0;
 
 pub static PIPE_HEIGHT_RANGE_SPR: [u32; 2] = [3, 9];
+pub static REACH_FLAP_INTERVAL: f32 = 0.25; // how often a player is expected to flap when climbing
 pub static PIPE_Y_GAP_SPR: u32 = 4;
 pub static PIPE_FLOOR_Y_SPR: i32 = -8; // -500
+pub static PIPE_COLUMN_SPR: u32 = (-PIPE_FLOOR_Y_SPR * 2) as u32; // pipe blocks and gap together
 
 pub static PIPE_WIDTH: u32 = 2;
 
+// coins
+pub static COIN_CHANCE: f32 = 0.4; // share of generated pipes holding a coin
+pub static COIN_OFF_LINE_CHANCE: f32 = 0.3; // share of coins placed away from the gap centre
+pub static COIN_PICKUP_RADIUS: f32 = SPRITE_SIZE * 0.6;
+pub static COIN_COLOR: [f32; 3] = [250.0, 190.0, 40.0];
+
+// power-ups
+pub static POWER_UP_CHANCE: f32 = 0.08; // share of generated pipes holding a power-up
+pub static POWER_UP_PICKUP_RADIUS: f32 = SPRITE_SIZE * 0.6;
+pub static SHIELD_SECS: f32 = 10.0;
+pub static SHIELD_GRACE_SECS: f32 = 1.0; // time to leave the pipe after the shield breaks
+pub static SLOW_TIME_SECS: f32 = 5.0;
+pub static SLOW_TIME_FACTOR: f32 = 0.6;
+pub static MAGNET_SECS: f32 = 8.0;
+pub static MAGNET_RADIUS: f32 = 250.0;
+pub static MAGNET_PULL_SPEED: f32 = 600.0;
+pub static SHRINK_SECS: f32 = 8.0;
+pub static SHRINK_SCALE: f32 = 0.6;
+pub static SHRINK_HITBOX_MARGIN: f32 = SPRITE_SIZE * 0.2;
+
+// skins
+pub static SKIN_CATALOG_PATH: &str = "skins/catalog.skins.ron";
+pub static TRAIL_INTERVAL: f32 = 0.03;
+
+// achievements
+pub static ACHIEVEMENTS_PATH: &str = "achievements/default.achievements.ron";
+
+// sounds
+pub static SOUND_SEED_SALT: u64 = 0x5eed_50d5; // sound variation is seeded from the run seed
+
+// music
+pub static MUSIC_LIST_PATH: &str = "music/default.music.ron";
+pub static MUSIC_CROSSFADE_SECS: f32 = 1.5;
+pub static MUSIC_PAUSED_LEVEL: f64 = 0.35; // of the music volume, while the game is paused
+pub static MUSIC_LOST_LEVEL: f64 = 0.2; // after the bird died
+pub static MUSIC_DUCK_SECS: f32 = 0.3;
+pub static MUSIC_LOST_FADE_SECS: f32 = 2.0;
+
+pub static DIFFICULTY: DifficultyProfile = DifficultyProfile {
+    narrow_gap_scores: [15, 60],
+    moving_pipes_score: 25,
+    moving_pipes_chance: 0.35,
+    moving_amplitude_spr: 1.5,
+    moving_period_secs: 3.0,
+};
+
 // player
 pub static PLAYER_X: f32 = -64.0;
 pub static PLAYER_START_Y: f32 = -200.0;
+pub static PHYSICS_FPS: f32 = 60.0; // frame rate the jump and gravity values are tuned for
 
-// clouds
-pub static CLOUDS_START_X: f32 = -SCREEN_X_BOUNDARY - SPRITE_SIZE * 3_f32;
-pub static CLOUDS_GAP_BETWEEN: f32 = 250.0;
-pub static CLOUDS_Y_RANGE: [f32; 2] = [-100.0, SCREEN_Y_BOUNDARY - 40.0];
+// background
+pub static SKY_DAY_SECS: f32 = 240.0;
+pub static SKY_DAY_SCORE: f32 = 120.0;
+pub static SKY_STARS: usize = 80;
+// dawn, noon, dusk and midnight
+pub static SKY_PALETTES: [SkyPalette; 4] = [
+    SkyPalette {
+        sky: [246.0, 168.0, 140.0],
+        tint: [255.0, 222.0, 205.0],
+        stars: 0.15,
+    },
+    SkyPalette {
+        sky: BACKGROUND_COLOR,
+        tint: [255.0, 255.0, 255.0],
+        stars: 0.0,
+    },
+    SkyPalette {
+        sky: [232.0, 118.0, 92.0],
+        tint: [238.0, 186.0, 170.0],
+        stars: 0.3,
+    },
+    SkyPalette {
+        sky: [18.0, 26.0, 58.0],
+        tint: [92.0, 104.0, 150.0],
+        stars: 1.0,
+    },
+];
+
+// weather
+pub static WEATHER_SEED_SALT: u64 = 0x5eed_3a7e; // random weather and gusts are seeded from the run seed
+pub static RAIN_PER_SEC: f32 = 120.0;
+pub static SNOW_PER_SEC: f32 = 40.0;
+pub static WIND_STREAKS_PER_SEC: f32 = 30.0;
+pub static WIND_GUST_INTERVAL_SECS: (f32, f32) = (6.0, 14.0);
+pub static WIND_WARNING_SECS: f32 = 1.5;
+pub static WIND_GUST_SECS: f32 = 1.2;
+pub static WIND_GUST_FORCE: f32 = 12.0; // added to the bird's vertical speed every second
+
+// themes, the first one is the default
+pub static THEMES: [&str; 4] = [
+    "themes/classic.theme.ron",
+    "themes/winter.theme.ron",
+    "themes/night_city.theme.ron",
+    "themes/retro.theme.ron",
+];
+// the look used until the theme is loaded, or if it fails to
+pub static CLASSIC_BIRD_PATH: &str = "sprites/bird.png";
+pub static CLASSIC_PIPE_BODY_PATH: &str = "sprites/pipe/body.png";
+pub static CLASSIC_PIPE_END_PATH: &str = "sprites/pipe/end.png";
+pub static CLASSIC_PARALLAX_PATH: &str = "backgrounds/classic.parallax.ron";
 
 // pkv data storage keys
 pub static PLAYER_STATS_KEY: &str = "player_stats";
 pub static GAME_SETTINGS_KEY: &str = "game_settings";
+
+// versus mode
+pub static RELAY_ADDRESS: &str = "127.0.0.1:7878";
+pub static CHECKSUM_INTERVAL: u32 = 10;
+
+// spectator mode
+pub static BROADCAST_ADDRESS: &str = "0.0.0.0:7879";
+pub static SPECTATE_ADDRESS: &str = "127.0.0.1:7879"; // overridden by the SPECTATE_ADDRESS env var
+
+// challenge modes
+pub static TIME_ATTACK_SECS: f32 = 60.0;
+pub static PIPE_RUSH_PIPES: i32 = 50;
+
+// levels
+pub static LEVELS: [&str; 3] = [
+    "levels/1.level.ron",
+    "levels/2.level.ron",
+    "levels/3.level.ron",
+];
+pub static LEVEL_MIN_PIPE_SPACING: f32 = 2.0 * SCREEN_X_BOUNDARY / (PIPES_NUMBER as f32);
+pub static LEVEL_FINISH_SPACING: f32 = 400.0;
+pub static PARKED_PIPE_X: f32 = 1.0e9; // unused pipes wait here once a level runs out
+
+// level editor
+pub static EDITOR_SCROLL_SPEED: f32 = 600.0;
+pub static EDITOR_MAX_PIPE_WIDTH: u32 = 8;
+pub static EDITOR_STAR_FLAPS_PER_PIPE: (f32, f32) = (4.0, 3.0); // for two and three stars
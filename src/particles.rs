@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::*;
+
+const PARTICLE_SIZE: f32 = 8.0;
+const PARTICLE_SPEED: [f32; 2] = [120.0, 320.0];
+const PARTICLE_LIFETIME: f32 = 0.5;
+const PARTICLE_GRAVITY: f32 = 600.0;
+const TRAIL_SIZE: f32 = 10.0;
+const TRAIL_LIFETIME: f32 = 0.4;
+
+#[derive(Component)]
+pub struct Particle {
+    velocity: Vec2,
+    gravity: f32,
+    age: f32,
+    lifetime: f32,
+}
+
+/// Spray `count` square particles of `color` out from `position`
+pub fn spawn_burst(commands: &mut Commands, position: Vec2, color: Color, count: usize) {
+    // purely visual, so it does not touch the seeded rng
+    let mut rng = thread_rng();
+
+    for _ in 0..count {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let speed = rng.gen_range(PARTICLE_SPEED[0]..PARTICLE_SPEED[1]);
+
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(PARTICLE_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position.extend(Z_PLAYER + 1.0)),
+                ..Default::default()
+            })
+            .insert(Particle {
+                velocity: Vec2::new(angle.cos(), angle.sin()) * speed,
+                gravity: PARTICLE_GRAVITY,
+                age: 0.0,
+                lifetime: PARTICLE_LIFETIME,
+            });
+    }
+}
+
+/// Leave a fading dot at `position` that drifts away with the pipes
+pub fn spawn_trail(commands: &mut Commands, position: Vec2, color: Color) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::splat(TRAIL_SIZE)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(position.extend(Z_PLAYER - 1.0)),
+            ..Default::default()
+        })
+        .insert(Particle {
+            velocity: Vec2::new(-PIPES_SPEED, 0.0),
+            gravity: 0.0,
+            age: 0.0,
+            lifetime: TRAIL_LIFETIME,
+        });
+}
+
+fn particles_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+    game_controller: Res<GameController>,
+    time: Res<Time>,
+) {
+    if game_controller.is_game_paused() {
+        return;
+    }
+
+    let delta_time = game_controller.delta_time(&time);
+
+    for (entity, mut particle, mut transform, mut sprite) in query.iter_mut() {
+        particle.age += delta_time;
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        particle.velocity.y -= particle.gravity * delta_time;
+        transform.translation += (particle.velocity * delta_time).extend(0.0);
+
+        // fade out over the lifetime
+        sprite.color.set_a(1.0 - particle.age / particle.lifetime);
+    }
+}
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(particles_system);
+    }
+}
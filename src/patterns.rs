@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::*;
+
+/// Named pipe sequences the generator strings together
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatternKind {
+    Random,      // independent heights
+    Staircase,   // steady climb or descent
+    Zigzag,      // alternating high and low gaps
+    TightTunnel, // barely changing heights
+    BigDrop,     // a slow climb followed by a sudden drop
+}
+
+/// Pipe heights from named patterns, kept within reach of the bird.
+///
+/// Only the seeded rng and the pipe count drive it, so clients sharing a seed
+/// get the same pipes regardless of their own score.
+#[derive(Clone)]
+pub struct PatternGenerator {
+    kind: PatternKind,
+    remaining: u32, // pipes left in the current pattern
+    step: i32,
+    last_height: Option<u32>,
+    generated: u32,
+}
+
+impl Default for PatternGenerator {
+    fn default() -> Self {
+        PatternGenerator {
+            kind: PatternKind::Random,
+            remaining: 0,
+            step: 0,
+            last_height: None,
+            generated: 0,
+        }
+    }
+}
+
+impl PatternGenerator {
+    pub fn next_height(&mut self, rng: &mut StdRng) -> u32 {
+        let [min, max] = PIPE_HEIGHT_RANGE_SPR.map(|height| height as i32);
+        let last = self
+            .last_height
+            .map_or((min + max) / 2, |height| height as i32);
+
+        if self.remaining == 0 {
+            self.start_pattern(rng, last);
+        }
+        self.remaining -= 1;
+
+        let target = match self.kind {
+            PatternKind::Random => rng.gen_range(min..=max),
+            PatternKind::Staircase => {
+                // turn around at the edges
+                if !(min..=max).contains(&(last + self.step)) {
+                    self.step = -self.step;
+                }
+                last + self.step
+            }
+            PatternKind::Zigzag => {
+                let target = last + self.step;
+                self.step = -self.step;
+                target
+            }
+            PatternKind::TightTunnel => last + rng.gen_range(-1..=1),
+            PatternKind::BigDrop if self.remaining == 0 => min,
+            PatternKind::BigDrop => last + 1,
+        };
+
+        // the pipe is reached once the previous ones were passed
+        let score = self.generated as i32;
+        let (rise, drop) = reach_sprites(DIFFICULTY.speed_multiplier(score));
+
+        // a moving pipe can carry its gap further away, and a narrower gap leaves less room
+        let moving = match score >= DIFFICULTY.moving_pipes_score {
+            true => DIFFICULTY.moving_amplitude_spr.ceil() as i32,
+            false => 0,
+        };
+        let narrowing = (PIPE_Y_GAP_SPR - DIFFICULTY.gap_sprites(score)) as i32;
+        let (rise, drop) = (
+            (rise - moving - narrowing).max(0),
+            (drop - moving - narrowing).max(0),
+        );
+
+        let height = match self.last_height {
+            Some(_) => target.clamp(last - drop, last + rise),
+            None => target,
+        }
+        .clamp(min, max) as u32;
+
+        self.last_height = Some(height);
+        self.generated += 1;
+
+        height
+    }
+
+    fn start_pattern(&mut self, rng: &mut StdRng, last: i32) {
+        let [min, max] = PIPE_HEIGHT_RANGE_SPR.map(|height| height as i32);
+        // head towards the side with more room
+        let direction = if last > (min + max) / 2 { -1 } else { 1 };
+
+        let (kind, length, step) = match rng.gen_range(0..10) {
+            0..=3 => (PatternKind::Random, rng.gen_range(2..=4), 0),
+            4 | 5 => (
+                PatternKind::Staircase,
+                rng.gen_range(4..=6),
+                direction * rng.gen_range(1..=2),
+            ),
+            6 | 7 => (
+                PatternKind::Zigzag,
+                rng.gen_range(4..=6),
+                direction * rng.gen_range(2..=4),
+            ),
+            8 => (PatternKind::TightTunnel, rng.gen_range(4..=6), 0),
+            _ => (PatternKind::BigDrop, rng.gen_range(3..=5), 0),
+        };
+
+        debug!("Next pipe pattern: {:?} for {} pipes", kind, length);
+
+        self.kind = kind;
+        self.remaining = length;
+        self.step = step;
+    }
+}
+
+/// Sprites the gap can rise and drop by between two pipes at the speed multiplier
+pub fn reach_sprites(speed_multiplier: f32) -> (i32, i32) {
+    let pipes_gap_between = 2.0 * SCREEN_X_BOUNDARY / (PIPES_NUMBER as f32);
+    let pipe_width = (PIPE_WIDTH as f32 * 0.5 + 0.5) * SPRITE_SIZE;
+
+    // time between leaving a gap and entering the next one
+    let free_time =
+        (pipes_gap_between - pipe_width - SPRITE_SIZE) / (PIPES_SPEED * speed_multiplier);
+
+    // delta_y is applied once per frame
+    let jump_speed = JUMP_FORCE * PHYSICS_FPS;
+    let gravity = GRAVITY * PHYSICS_FPS;
+
+    // flapping steadily keeps the bird climbing, not flapping drops it
+    let climb_speed = jump_speed - 0.5 * gravity * REACH_FLAP_INTERVAL;
+    let rise = climb_speed * free_time;
+    let drop = 0.5 * gravity * free_time * free_time;
+
+    (
+        ((rise / SPRITE_SIZE) as i32).max(1),
+        ((drop / SPRITE_SIZE) as i32).max(1),
+    )
+}
@@ -1,124 +1,166 @@
-use std::f32::consts::E;
+use std::f32::consts::TAU;
 
 use bevy::prelude::*;
-use bevy_kira_audio::prelude::*;
 use rand::prelude::*;
 
+use crate::coins::Coin;
+use crate::events::{NewHighScore, PipePassed, PlayerHit};
+use crate::levels::LevelPipe;
+use crate::network::protocol::{PipeMovement, PipeSnapshot};
+use crate::powerups::{PowerUpKind, PowerUpPickup, Shield, Shrink, SlowTime};
 use crate::*;
 
-fn pipes_setup(mut commands: Commands, pipes_handler: Res<PipesHandler>) {
+fn pipes_setup(
+    mut commands: Commands,
+    pipes_handler: Res<PipesHandler>,
+    mut game_controller: ResMut<GameController>,
+) {
     let pipes_gap_between = 2.0 * SCREEN_X_BOUNDARY / (PIPES_NUMBER as f32);
 
     // Spawn pipes
     for i in 0..PIPES_NUMBER {
         spawn_pipe(
             &mut commands,
+            &mut game_controller,
             &pipes_handler,
+            i as usize,
             PIPES_START_X + i as f32 * pipes_gap_between,
         );
     }
 }
 
-#[allow(clippy::type_complexity)]
 pub fn pipes_system(
     mut commands: Commands,
 
-    (mut player_query, mut pipes_query, mut block_query): (
-        Query<(&mut Player, &mut Transform)>,
-        Query<&mut PipeParent>,
-        Query<(&mut PipeBlock, &mut Transform), Without<Player>>,
+    mut player_query: Query<(
+        &mut Player,
+        &mut Transform,
+        Option<&mut Shield>,
+        Option<&Shrink>,
+        Option<&SlowTime>,
+    )>,
+    mut pipes_query: PipesQuery,
+
+    (mut passed_events, mut high_score_events, mut hit_events): (
+        EventWriter<PipePassed>,
+        EventWriter<NewHighScore>,
+        EventWriter<PlayerHit>,
     ),
 
     mut game_controller: ResMut<GameController>,
     time: Res<Time>,
-    audio: Res<Audio>,
     pipes_handler: Res<PipesHandler>,
 ) {
-    let delta_time: f32 = time.delta().as_secs_f32();
+    let delta_time: f32 = game_controller.delta_time(&time);
 
     // get the player and atlas handle
-    let (mut player, player_transform) = player_query.single_mut();
+    let (mut player, player_transform, mut shield, shrink, slow_time) = player_query.single_mut();
+
+    // the bird collides at its centre, shrinking lets it graze the pipes
+    let hitbox_margin = shrink.map_or(0.0, |_| SHRINK_HITBOX_MARGIN);
 
     if game_controller.is_game_running() {
         // let pipes_speed_multiplier = 1.2 + ((game_controller.score / 10) as f32 / 10.0);
-        let pipes_delta_x = -PIPES_SPEED * game_controller.speed_multiplier * delta_time;
+        let speed_multiplier =
+            game_controller.speed_multiplier * slow_time.map_or(1.0, |_| SLOW_TIME_FACTOR);
+        let pipes_delta_x = -PIPES_SPEED * speed_multiplier * delta_time;
+        game_controller.distance -= pipes_delta_x;
         println!(
             "pipes_speed: {}, score: {}",
             game_controller.speed_multiplier, game_controller.score
         );
 
-        // update pipe blocks
-        for (_, mut transform) in block_query.iter_mut() {
+        // update pipes, their blocks follow the parent transform
+        for (entity, mut pipe, mut transform) in pipes_query.iter_mut() {
             transform.translation.x += pipes_delta_x;
-        }
 
-        // update pipes
-        for mut pipe in pipes_query.iter_mut() {
-            pipe.x += pipes_delta_x;
+            // move the gap of oscillating pipes
+            if let Some(y_offset) = pipe.update_movement(delta_time) {
+                transform.translation.y = y_offset;
+            }
 
             // check if pipe off screen
-            if pipe.x < -SCREEN_X_BOUNDARY {
+            if transform.translation.x < -SCREEN_X_BOUNDARY {
                 pipe.reset(
                     &mut commands,
-                    &game_controller,
+                    (entity, &mut *transform),
+                    &mut game_controller,
                     &pipes_handler,
                     SCREEN_X_BOUNDARY + SPRITE_SIZE,
                 );
             }
 
+            let pipe_x = transform.translation.x;
+            let y_offset = transform.translation.y;
+
             // check if player gained point
             if !pipe.passed_score
-                && pipe.x - (pipe.width_sprites * SPRITE_SIZE / 2.0)
+                && pipe_x - (pipe.width_sprites * SPRITE_SIZE / 2.0)
                     < player_transform.translation.x - SPRITE_SIZE
             {
                 pipe.passed_score = true;
                 game_controller.score += 1;
 
-                let a = 0.5;
-                let b = -0.5;
-                let c = 25.0;
-                game_controller.speed_multiplier =
-                    1.0 / (a + E.powf(-(game_controller.score as f32 / c) + b));
+                // levels keep the speed they were authored with
+                if !game_controller.mode.plays_level() {
+                    game_controller.speed_multiplier =
+                        DIFFICULTY.speed_multiplier(game_controller.score);
+                }
 
                 // /*= (1.0 + 1.0/((game_controller.score as f32 * 3.0).powf(2.0) + 1.0)).powf(0.6);
 
-                // play the score sound if high score passed
-                if game_controller.score == game_controller.player_stats.high_score + 1 {
-                    audio
-                        .play(pipes_handler.score_sound.clone())
-                        .with_volume(game_controller.settings.effects_vol_level);
+                passed_events.send(PipePassed {
+                    score: game_controller.score,
+                });
+
+                // the score to beat was just passed
+                if game_controller
+                    .target_score()
+                    .map_or(false, |target| game_controller.score == target + 1)
+                {
+                    high_score_events.send(NewHighScore {
+                        score: game_controller.score,
+                    });
                 }
             }
 
+            let half_width = pipe.width_sprites * SPRITE_SIZE / 2.0 - hitbox_margin;
+            let inside_pipe = pipe_x - half_width < player_transform.translation.x
+                && pipe_x + half_width > player_transform.translation.x;
+
             // check if player touches bottom pipe
-            if pipe.x - (pipe.width_sprites * SPRITE_SIZE / 2.0) < player_transform.translation.x
-                && pipe.x + (pipe.width_sprites * SPRITE_SIZE / 2.0)
-                    > player_transform.translation.x
-                && (PIPE_FLOOR_Y_SPR + pipe.height_sprites as i32) as f32 * SPRITE_SIZE
-                    >= player_transform.translation.y
-            {
-                player.dead = true;
-            }
+            let touches_bottom =
+                (PIPE_FLOOR_Y_SPR + pipe.height_sprites as i32) as f32 * SPRITE_SIZE + y_offset
+                    - hitbox_margin
+                    >= player_transform.translation.y;
 
             // check if player touches top pipe
-            if pipe.x - (pipe.width_sprites * SPRITE_SIZE / 2.0) < player_transform.translation.x
-                && pipe.x + (pipe.width_sprites * SPRITE_SIZE / 2.0)
-                    > player_transform.translation.x
-                && (PIPE_FLOOR_Y_SPR + pipe.height_sprites as i32 + pipe.y_gap_sprites as i32)
-                    as f32
+            let touches_top =
+                (PIPE_FLOOR_Y_SPR + pipe.height_sprites as i32 + pipe.y_gap_sprites as i32) as f32
                     * SPRITE_SIZE
-                    <= player_transform.translation.y
-            {
-                player.dead = true;
+                    + y_offset
+                    + hitbox_margin
+                    <= player_transform.translation.y;
+
+            if inside_pipe && (touches_bottom || touches_top) {
+                match shield.as_mut() {
+                    Some(shield) => shield.absorb(),
+                    None => {
+                        if player.hit() {
+                            hit_events.send(PlayerHit);
+                        }
+                    }
+                }
             }
         }
     }
 }
 
 pub struct PipesHandler {
-    texture_body: Handle<Image>,
-    texture_end: Handle<Image>,
-    score_sound: Handle<AudioSource>,
+    pub texture_body: Handle<Image>, // swapped for the skin and theme once they are loaded
+    pub texture_end: Handle<Image>,
+    texture_coin: Handle<Image>,
+    texture_power_ups: Vec<Handle<Image>>, // in the order of `PowerUpKind::ALL`
 }
 
 impl FromWorld for PipesHandler {
@@ -126,74 +168,397 @@ impl FromWorld for PipesHandler {
         let asset_server = world.get_resource::<AssetServer>().unwrap();
 
         PipesHandler {
-            texture_body: asset_server.load("sprites/pipe/body.png"),
-            texture_end: asset_server.load("sprites/pipe/end.png"),
-            score_sound: asset_server.load("sounds/score.wav"),
+            texture_body: asset_server.load(CLASSIC_PIPE_BODY_PATH),
+            texture_end: asset_server.load(CLASSIC_PIPE_END_PATH),
+            texture_coin: asset_server.load("sprites/coin.png"),
+            texture_power_ups: PowerUpKind::ALL
+                .iter()
+                .map(|kind| asset_server.load(kind.texture_path()))
+                .collect(),
         }
     }
 }
 
+/// Pipes with the transform their blocks are children of
+pub type PipesQuery<'w, 's> =
+    Query<'w, 's, (Entity, &'static mut PipeParent, &'static mut Transform), Without<Player>>;
+
+/// The pipe entity and its transform, which holds the pipe position and gap offset
+pub type PipeEntity<'a> = (Entity, &'a mut Transform);
+
 #[derive(Component)]
 pub struct PipeParent {
-    pub x: f32,
+    index: usize, // position among the pipes, keys them in snapshots
     height_sprites: u32,
+    width_columns: u32, // half-sprite columns the pipe is built from
     width_sprites: f32, // has to be f32, because of 1.5
     y_gap_sprites: u32,
     // pub passed: bool,
     passed_score: bool, // give score in the middle of the pipe
+    movement: Option<PipeMovement>,
     blocks: Vec<Entity>,
+    coin: Option<Entity>, // kept hidden while the pipe holds no coin
+    power_up: Option<Entity>,
 }
 
 #[derive(Component)]
-pub struct PipeBlock;
+pub struct PipeBlock {
+    pub end: bool, // the rim at the gap, drawn with `PipesHandler::texture_end`
+}
 
 impl PipeParent {
     pub fn reset(
         &mut self,
         commands: &mut Commands,
-        game_controller: &GameController,
+        (entity, transform): PipeEntity,
+        game_controller: &mut GameController,
         pipes_handler: &PipesHandler,
         x: f32,
     ) {
-        self.y_gap_sprites = PIPE_Y_GAP_SPR;
-        // reduce gap if score above
-        if game_controller.score > 15 {
-            self.y_gap_sprites = PIPE_Y_GAP_SPR - 1;
-        }
-        if game_controller.score > 60 {
-            self.y_gap_sprites = PIPE_Y_GAP_SPR - 2;
+        if game_controller.mode.plays_level() {
+            self.reset_from_level(
+                commands,
+                (entity, transform),
+                game_controller,
+                pipes_handler,
+            );
+            return;
         }
 
+        // reduce gap if score above
+        self.y_gap_sprites = DIFFICULTY.gap_sprites(game_controller.score);
+        self.width_columns = PIPE_WIDTH;
+
         // self.passed = false;
         self.passed_score = false;
 
-        let mut rng = thread_rng();
-
-        let new_height = rng.gen_range(PIPE_HEIGHT_RANGE_SPR[0]..=PIPE_HEIGHT_RANGE_SPR[1]);
+        let new_height = game_controller.next_pipe_height();
         // let new_x = SCREEN_X_BOUNDARY + SPRITE_SIZE;
 
-        self.x = x;
+        transform.translation = Vec3::new(x, 0.0, 0.0);
         self.height_sprites = new_height;
 
-        // despawn old blocks
-        for block in self.blocks.iter_mut() {
-            commands.entity(*block).despawn();
+        self.movement = DIFFICULTY
+            .roll_movement(game_controller.score, &mut game_controller.rng)
+            .map(|movement| PipeMovement {
+                amplitude: movement.amplitude.min(self.max_gap_offset()),
+                ..movement
+            })
+            .filter(|movement| movement.amplitude >= SPRITE_SIZE / 4.0);
+
+        // rebuild the pipe from its old blocks
+        self.spawn_blocks(commands, entity, pipes_handler);
+        self.roll_pickups(commands, entity, game_controller, pipes_handler);
+
+        game_controller.log_pipe(&self.snapshot(transform));
+    }
+
+    /// Roll the coin or power-up the pipe holds from the seeded rng
+    fn roll_pickups(
+        &mut self,
+        commands: &mut Commands,
+        entity: Entity,
+        game_controller: &mut GameController,
+        pipes_handler: &PipesHandler,
+    ) {
+        // always roll, so the pipes after it stay the same
+        let power_up_roll: f32 = game_controller.rng.gen();
+        let power_up_index = game_controller.rng.gen_range(0..PowerUpKind::ALL.len());
+        let coin_roll: f32 = game_controller.rng.gen();
+        let coin_offset: f32 = game_controller.rng.gen_range(-1.0..1.0);
+
+        let power_up = (power_up_roll < POWER_UP_CHANCE).then(|| PowerUpKind::ALL[power_up_index]);
+        // a power-up takes the place of the coin
+        let coin_y = (power_up.is_none() && coin_roll < COIN_CHANCE).then(|| {
+            if coin_roll < COIN_CHANCE * COIN_OFF_LINE_CHANCE {
+                // off the ideal line, but still inside the gap
+                self.gap_center_y()
+                    + coin_offset * (self.y_gap_sprites as f32 - 1.0) * SPRITE_SIZE / 2.0
+            } else {
+                self.gap_center_y()
+            }
+        });
+        self.place_coin(commands, entity, pipes_handler, coin_y);
+        self.place_power_up(commands, entity, pipes_handler, power_up);
+    }
+
+    /// Take the next pipe of the level, or park the pipe once the level runs out
+    fn reset_from_level(
+        &mut self,
+        commands: &mut Commands,
+        (entity, transform): PipeEntity,
+        game_controller: &mut GameController,
+        pipes_handler: &PipesHandler,
+    ) {
+        let (level_x, level_pipe) = match game_controller.next_level_pipe() {
+            Some(next) => next,
+            None => {
+                self.hide_blocks(commands, 0);
+                self.clear_pickups(commands, entity, pipes_handler);
+                transform.translation = Vec3::new(PARKED_PIPE_X, 0.0, 0.0);
+                self.passed_score = true;
+                self.movement = None;
+                return;
+            }
+        };
+
+        let x = level_x - game_controller.distance + PIPES_START_X;
+        transform.translation = Vec3::new(x, 0.0, 0.0);
+
+        self.set_level_pipe(&level_pipe);
+        self.spawn_blocks(commands, entity, pipes_handler);
+        self.clear_pickups(commands, entity, pipes_handler);
+    }
+
+    /// Pipe as a level entry will be played, used for the level editor preview
+    pub fn from_level_pipe(level_pipe: &LevelPipe) -> Self {
+        let mut pipe = PipeParent {
+            index: 0,
+            height_sprites: 0,
+            width_columns: PIPE_WIDTH,
+            width_sprites: 0.0,
+            y_gap_sprites: PIPE_Y_GAP_SPR,
+            passed_score: false,
+            movement: None,
+            blocks: Vec::new(),
+            coin: None,
+            power_up: None,
+        };
+        pipe.set_level_pipe(level_pipe);
+
+        pipe
+    }
+
+    pub fn set_level_pipe(&mut self, level_pipe: &LevelPipe) {
+        self.passed_score = false;
+        // keep hand-written values within the screen
+        self.y_gap_sprites = level_pipe.gap.clamp(1, PIPE_COLUMN_SPR);
+        self.height_sprites = level_pipe.height.min(PIPE_COLUMN_SPR - self.y_gap_sprites);
+        self.width_columns = level_pipe.width.max(1);
+
+        self.movement = level_pipe.movement.map(|movement| PipeMovement {
+            amplitude: (movement.amplitude * SPRITE_SIZE).min(self.max_gap_offset()),
+            period: movement.period,
+            phase: movement.phase * TAU,
+        });
+    }
+
+    pub fn snapshot(&self, transform: &Transform) -> PipeSnapshot {
+        PipeSnapshot {
+            index: Some(self.index),
+            x: transform.translation.x,
+            height: self.height_sprites,
+            gap: self.y_gap_sprites,
+            width: self.width_columns,
+            passed: self.passed_score,
+            y_offset: transform.translation.y,
+            movement: self.movement,
         }
-        self.blocks.clear();
+    }
+
+    /// The snapshot of this pipe among `snapshots`, matched by position if they are not keyed
+    pub fn find_snapshot<'a>(&self, snapshots: &'a [PipeSnapshot]) -> Option<&'a PipeSnapshot> {
+        snapshots
+            .iter()
+            .enumerate()
+            .find(|(i, snapshot)| snapshot.index.unwrap_or(*i) == self.index)
+            .map(|(_, snapshot)| snapshot)
+    }
 
-        // spawn new blocks
-        self.spawn_blocks(commands, pipes_handler);
+    /// Rebuild the pipe exactly as it was when `snapshot` was taken
+    pub fn restore(
+        &mut self,
+        commands: &mut Commands,
+        (entity, transform): PipeEntity,
+        pipes_handler: &PipesHandler,
+        snapshot: &PipeSnapshot,
+    ) {
+        transform.translation = Vec3::new(snapshot.x, snapshot.y_offset, 0.0);
+        self.height_sprites = snapshot.height;
+        self.y_gap_sprites = snapshot.gap;
+        self.width_columns = snapshot.width;
+        self.passed_score = snapshot.passed;
+        self.movement = snapshot.movement;
+
+        // pickups are not part of the snapshot
+        self.spawn_blocks(commands, entity, pipes_handler);
+        self.clear_pickups(commands, entity, pipes_handler);
     }
 
-    fn spawn_blocks(&mut self, commands: &mut Commands, pipes_handler: &PipesHandler) {
+    /// Mirror a pipe received from a broadcasting game
+    pub fn apply_snapshot(
+        &mut self,
+        commands: &mut Commands,
+        (entity, transform): PipeEntity,
+        pipes_handler: &PipesHandler,
+        snapshot: &PipeSnapshot,
+    ) {
+        if self.height_sprites != snapshot.height
+            || self.y_gap_sprites != snapshot.gap
+            || self.width_columns != snapshot.width
+        {
+            self.restore(commands, (entity, transform), pipes_handler, snapshot);
+        } else {
+            transform.translation = Vec3::new(snapshot.x, snapshot.y_offset, 0.0);
+        }
+    }
+
+    /// Advance the gap oscillation, returning the new gap offset
+    fn update_movement(&mut self, delta_time: f32) -> Option<f32> {
+        let movement = self.movement.as_mut()?;
+
+        movement.phase = (movement.phase + delta_time * TAU / movement.period) % TAU;
+
+        Some(movement.amplitude * movement.phase.sin())
+    }
+
+    /// How far the gap can move before leaving the screen
+    fn max_gap_offset(&self) -> f32 {
+        let gap_bottom = (PIPE_FLOOR_Y_SPR + self.height_sprites as i32) as f32 * SPRITE_SIZE;
+        let gap_top = gap_bottom + self.y_gap_sprites as f32 * SPRITE_SIZE;
+
+        (SCREEN_Y_BOUNDARY - gap_top)
+            .min(gap_bottom + SCREEN_Y_BOUNDARY)
+            .max(0.0)
+    }
+
+    /// Gap centre relative to the pipe transform
+    fn gap_center_y(&self) -> f32 {
+        (PIPE_FLOOR_Y_SPR as f32 + self.height_sprites as f32 + self.y_gap_sprites as f32 / 2.0)
+            * SPRITE_SIZE
+    }
+
+    /// Reuse the pickup child in `slot`, spawning it the first time
+    fn pickup_entity(commands: &mut Commands, parent: Entity, slot: &mut Option<Entity>) -> Entity {
+        *slot.get_or_insert_with(|| {
+            let pickup = commands.spawn_bundle(SpriteBundle::default()).id();
+            commands.entity(parent).add_child(pickup);
+            pickup
+        })
+    }
+
+    /// Show the coin at the height `y` in the gap, or hide it for `None`
+    fn place_coin(
+        &mut self,
+        commands: &mut Commands,
+        parent: Entity,
+        pipes_handler: &PipesHandler,
+        y: Option<f32>,
+    ) {
+        let coin = Self::pickup_entity(commands, parent, &mut self.coin);
+
+        let x = (self.width_columns - 1) as f32 * 0.25 * SPRITE_SIZE;
+        commands
+            .entity(coin)
+            .insert(pipes_handler.texture_coin.clone())
+            .insert(Transform::from_xyz(x, y.unwrap_or(0.0), Z_PIPE + 1.0))
+            .insert(Visibility {
+                is_visible: y.is_some(),
+            })
+            .insert(Coin {
+                collected: y.is_none(),
+            });
+    }
+
+    /// Show the power-up in the middle of the gap, or hide it for `None`
+    fn place_power_up(
+        &mut self,
+        commands: &mut Commands,
+        parent: Entity,
+        pipes_handler: &PipesHandler,
+        kind: Option<PowerUpKind>,
+    ) {
+        let pickup = Self::pickup_entity(commands, parent, &mut self.power_up);
+
+        let x = (self.width_columns - 1) as f32 * 0.25 * SPRITE_SIZE;
+        let mut pickup_commands = commands.entity(pickup);
+        pickup_commands
+            .insert(Transform::from_xyz(x, self.gap_center_y(), Z_PIPE + 1.0))
+            .insert(Visibility {
+                is_visible: kind.is_some(),
+            });
+
+        match kind {
+            Some(kind) => {
+                let index = PowerUpKind::ALL.iter().position(|k| *k == kind).unwrap();
+                pickup_commands
+                    .insert(pipes_handler.texture_power_ups[index].clone())
+                    .insert(PowerUpPickup {
+                        kind,
+                        collected: false,
+                    });
+            }
+            None => {
+                pickup_commands.remove::<PowerUpPickup>();
+            }
+        }
+    }
+
+    fn clear_pickups(
+        &mut self,
+        commands: &mut Commands,
+        parent: Entity,
+        pipes_handler: &PipesHandler,
+    ) {
+        self.place_coin(commands, parent, pipes_handler, None);
+        self.place_power_up(commands, parent, pipes_handler, None);
+    }
+
+    /// Hide the blocks past `used`, they are kept for the next build
+    fn hide_blocks(&mut self, commands: &mut Commands, used: usize) {
+        for block in self.blocks.iter().skip(used) {
+            commands
+                .entity(*block)
+                .insert(Visibility { is_visible: false });
+        }
+    }
+
+    /// Reuse the block entity at `index` for the sprite, spawning a child if there are too few
+    fn place_block(
+        &mut self,
+        commands: &mut Commands,
+        parent: Entity,
+        index: usize,
+        sprite: SpriteBundle,
+        end: bool,
+    ) {
+        match self.blocks.get(index) {
+            Some(block) => {
+                commands
+                    .entity(*block)
+                    .insert_bundle(sprite)
+                    .insert(PipeBlock { end });
+            }
+            None => {
+                let block = commands
+                    .spawn()
+                    .insert_bundle(sprite)
+                    .insert(PipeBlock { end })
+                    .id();
+                commands.entity(parent).add_child(block);
+                self.blocks.push(block);
+            }
+        }
+    }
+
+    /// Build the pipe as children of `parent`, reusing the blocks of its previous build
+    pub fn spawn_blocks(
+        &mut self,
+        commands: &mut Commands,
+        parent: Entity,
+        pipes_handler: &PipesHandler,
+    ) {
+        let mut used = 0;
+
         // spawn bottom pipe
         for i in 0..self.height_sprites {
-            for j in 0..PIPE_WIDTH {
-                let block_x = self.x + j as f32 * 0.5 * SPRITE_SIZE;
-                self.width_sprites = (block_x - self.x + SPRITE_SIZE) / SPRITE_SIZE;
+            for j in 0..self.width_columns {
+                let block_x = j as f32 * 0.5 * SPRITE_SIZE;
+                self.width_sprites = (block_x + SPRITE_SIZE) / SPRITE_SIZE;
 
-                let flip_x = j + 1 == PIPE_WIDTH;
-                let texture = if i + 1 == self.height_sprites {
+                let flip_x = j + 1 == self.width_columns;
+                let end = i + 1 == self.height_sprites;
+                let texture = if end {
                     &pipes_handler.texture_end
                 } else {
                     &pipes_handler.texture_body
@@ -203,34 +568,35 @@ impl PipeParent {
                     ..Default::default()
                 };
 
-                self.blocks.push(
-                    commands
-                        .spawn()
-                        .insert_bundle(SpriteBundle {
-                            texture: texture.clone(),
-                            transform: Transform::from_translation(Vec3::new(
-                                block_x,
-                                (PIPE_FLOOR_Y_SPR + i as i32) as f32 * SPRITE_SIZE,
-                                Z_PIPE,
-                            )),
-                            sprite,
-                            ..Default::default()
-                        })
-                        .insert(PipeBlock)
-                        .id(),
+                self.place_block(
+                    commands,
+                    parent,
+                    used,
+                    SpriteBundle {
+                        texture: texture.clone(),
+                        transform: Transform::from_translation(Vec3::new(
+                            block_x,
+                            (PIPE_FLOOR_Y_SPR + i as i32) as f32 * SPRITE_SIZE,
+                            Z_PIPE,
+                        )),
+                        sprite,
+                        ..Default::default()
+                    },
+                    end,
                 );
+                used += 1;
             }
         }
 
         // spawn top pipe
-        let top_blocks =
-            ((-PIPE_FLOOR_Y_SPR) * 2) as u32 - self.height_sprites - self.y_gap_sprites;
+        let top_blocks = PIPE_COLUMN_SPR - self.height_sprites - self.y_gap_sprites;
         for i in 0..top_blocks {
-            for j in 0..PIPE_WIDTH {
-                let block_x = self.x + j as f32 * 0.5 * SPRITE_SIZE;
+            for j in 0..self.width_columns {
+                let block_x = j as f32 * 0.5 * SPRITE_SIZE;
 
-                let flip_x = j + 1 == PIPE_WIDTH;
-                let texture = if i + 1 == top_blocks {
+                let flip_x = j + 1 == self.width_columns;
+                let end = i + 1 == top_blocks;
+                let texture = if end {
                     &pipes_handler.texture_end
                 } else {
                     &pipes_handler.texture_body
@@ -241,47 +607,65 @@ impl PipeParent {
                     ..Default::default()
                 };
 
-                self.blocks.push(
-                    commands
-                        .spawn()
-                        .insert_bundle(SpriteBundle {
-                            texture: texture.clone(),
-                            transform: Transform::from_translation(Vec3::new(
-                                block_x,
-                                (-PIPE_FLOOR_Y_SPR - i as i32) as f32 * SPRITE_SIZE,
-                                Z_PIPE,
-                            )),
-                            sprite,
-                            ..Default::default()
-                        })
-                        .insert(PipeBlock)
-                        .id(),
+                self.place_block(
+                    commands,
+                    parent,
+                    used,
+                    SpriteBundle {
+                        texture: texture.clone(),
+                        transform: Transform::from_translation(Vec3::new(
+                            block_x,
+                            (-PIPE_FLOOR_Y_SPR - i as i32) as f32 * SPRITE_SIZE,
+                            Z_PIPE,
+                        )),
+                        sprite,
+                        ..Default::default()
+                    },
+                    end,
                 );
+                used += 1;
             }
         }
+
+        self.hide_blocks(commands, used);
     }
 }
 
-pub fn spawn_pipe(commands: &mut Commands, pipes_handler: &PipesHandler, x: f32) {
-    let mut rng = thread_rng();
-    let height = rng.gen_range(PIPE_HEIGHT_RANGE_SPR[0]..=PIPE_HEIGHT_RANGE_SPR[1]);
-
-    let blocks: Vec<Entity> = Vec::new();
+pub fn spawn_pipe(
+    commands: &mut Commands,
+    game_controller: &mut GameController,
+    pipes_handler: &PipesHandler,
+    index: usize,
+    x: f32,
+) {
+    let height = game_controller.next_pipe_height();
 
     let mut pipe = PipeParent {
-        x,
+        index,
         height_sprites: height,
+        width_columns: PIPE_WIDTH,
         width_sprites: 0.0,
         y_gap_sprites: PIPE_Y_GAP_SPR,
         passed_score: false,
-        blocks,
+        movement: None,
+        blocks: Vec::new(),
+        coin: None,
+        power_up: None,
     };
 
-    // spawn pipe blocks
-    pipe.spawn_blocks(commands, pipes_handler);
-
     // spawn pipe parent
-    commands.spawn().insert(pipe);
+    let parent = commands
+        .spawn_bundle(SpatialBundle::from_transform(Transform::from_xyz(
+            x, 0.0, 0.0,
+        )))
+        .id();
+
+    // spawn pipe blocks and pickups
+    pipe.spawn_blocks(commands, parent, pipes_handler);
+    pipe.roll_pickups(commands, parent, game_controller, pipes_handler);
+    game_controller.log_pipe(&pipe.snapshot(&Transform::from_xyz(x, 0.0, 0.0)));
+
+    commands.entity(parent).insert(pipe);
 }
 
 pub struct PipesPlugin;
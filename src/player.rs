@@ -1,10 +1,10 @@
 use bevy::prelude::*;
-use bevy_kira_audio::prelude::*;
 
+use crate::events::{Flapped, GamePaused, GameReset, PlayerHit, PlayerLanded, RestartRequested};
 use crate::*;
 
-static JUMP_FORCE: f32 = 10.0;
-static GRAVITY: f32 = 30.0;
+pub static JUMP_FORCE: f32 = 10.0;
+pub static GRAVITY: f32 = 30.0;
 
 fn player_setup(mut commands: Commands, player_handler: Res<PlayerHandler>) {
     // Spawn the player
@@ -22,9 +22,8 @@ fn player_setup(mut commands: Commands, player_handler: Res<PlayerHandler>) {
         .insert(Player {
             delta_y: 0.0,
             dead: false,
-            hit_sound: false,
-            lose_sound: false,
             animation: PlayerAnimation::Idle,
+            idle_y: PLAYER_START_Y,
         });
 }
 
@@ -32,25 +31,35 @@ fn player_system(
     mut commands: Commands,
 
     mut query: Query<(&mut Player, &mut Transform)>,
-    mut pipes_query: Query<&mut PipeParent>,
-
-    (pkv, time, keyboard_input, audio): (
-        ResMut<PkvStore>,
-        Res<Time>,
-        Res<Input<KeyCode>>,
-        Res<Audio>,
-    ),
-    (mut game_controller, pipes_handler, player_handler): (
-        ResMut<GameController>,
-        Res<PipesHandler>,
-        Res<PlayerHandler>,
+    mut pipes_query: PipesQuery,
+
+    (mut flap_events, mut hit_events, mut landed_events, mut reset_events, mut pause_events): (
+        EventWriter<Flapped>,
+        EventWriter<PlayerHit>,
+        EventWriter<PlayerLanded>,
+        EventWriter<GameReset>,
+        EventWriter<GamePaused>,
     ),
+    mut restart_events: EventReader<RestartRequested>,
+
+    (mut pkv, time, keyboard_input): (ResMut<PkvStore>, Res<Time>, Res<Input<KeyCode>>),
+    (mut game_controller, pipes_handler): (ResMut<GameController>, Res<PipesHandler>),
 ) {
     const MIN_ROTATION: f32 = -0.4;
     const MAX_ROTATION: f32 = 0.4;
     const ROTATION_SPEED: f32 = 3.0;
 
-    let delta_time: f32 = time.delta().as_secs_f32();
+    let delta_time: f32 = game_controller.delta_time(&time);
+
+    let restart = restart_events.iter().count() > 0;
+
+    // the bird is driven by the remote game while spectating, and waits while editing
+    if !restart
+        && (game_controller.game_state == GameState::Spectating
+            || game_controller.game_state == GameState::Editing)
+    {
+        return;
+    }
 
     // get the player
     let (mut player, mut transform) = query.single_mut();
@@ -60,12 +69,11 @@ fn player_system(
         && (game_controller.is_game_running() || game_controller.game_state == GameState::Waiting)
     {
         player.delta_y = JUMP_FORCE;
-        game_controller.game_state = GameState::Started;
-
-        // play the jump sound
-        audio
-            .play(player_handler.jump_sound.clone())
-            .with_volume(game_controller.settings.effects_vol_level * 0.5);
+        if !game_controller.is_game_running() {
+            game_controller.start_game(&mut pkv);
+        }
+        game_controller.flaps += 1;
+        flap_events.send(Flapped);
 
         // jump animation
         player.animation = PlayerAnimation::Jump;
@@ -77,7 +85,9 @@ fn player_system(
     if game_controller.is_game_running() {
         // apply gravity
         player.delta_y -= GRAVITY * delta_time;
-    } else if game_controller.game_state == GameState::Waiting {
+    } else if game_controller.game_state == GameState::Waiting
+        || game_controller.game_state == GameState::Connecting
+    {
         // idle animation
         player.animation = PlayerAnimation::Idle;
     }
@@ -86,7 +96,7 @@ fn player_system(
     if !game_controller.is_game_paused() {
         match player.animation {
             PlayerAnimation::Idle => {
-                if transform.translation.y > PLAYER_START_Y - 20.0 {
+                if transform.translation.y > player.idle_y - 20.0 {
                     player.delta_y -= GRAVITY * delta_time / 4.0;
                 } else {
                     player.delta_y += GRAVITY * delta_time / 2.0;
@@ -97,19 +107,6 @@ fn player_system(
                 transform.rotate_z(rotation);
             }
             PlayerAnimation::Death => {
-                if !player.hit_sound {
-                    audio
-                        .play(player_handler.hit_sound.clone())
-                        .with_volume(game_controller.settings.effects_vol_level);
-                    player.hit_sound = true;
-                }
-                if game_controller.is_game_finished(&transform) && !player.lose_sound {
-                    audio
-                        .play(player_handler.lose_sound.clone())
-                        .with_volume(2.0 * game_controller.settings.effects_vol_level);
-                    player.lose_sound = true;
-                }
-
                 player.delta_y -= GRAVITY * 2.0 * delta_time;
 
                 if transform.rotation.z > MIN_ROTATION * 1.4 {
@@ -125,45 +122,76 @@ fn player_system(
         }
     }
 
+    let was_on_screen = transform.translation.y >= -SCREEN_Y_BOUNDARY;
     if !game_controller.is_game_paused() {
-        transform.translation.y += player.delta_y;
+        transform.translation.y += player.delta_y * game_controller.time_scale;
     }
 
     // check if player off screen
-    if transform.translation.y < -SCREEN_Y_BOUNDARY || transform.translation.y > SCREEN_Y_BOUNDARY {
-        player.dead = true;
+    if (transform.translation.y < -SCREEN_Y_BOUNDARY || transform.translation.y > SCREEN_Y_BOUNDARY)
+        && player.hit()
+    {
+        hit_events.send(PlayerHit);
+    }
+
+    // the dead bird just fell out of the screen
+    if player.dead && was_on_screen && transform.translation.y < -SCREEN_Y_BOUNDARY {
+        landed_events.send(PlayerLanded);
     }
 
     // check if player dead
     if player.dead {
-        if game_controller.game_state != GameState::Restart {
-            game_controller.game_state = GameState::Finished;
-        }
+        game_controller.game_state = GameState::Finished;
         player.animation = PlayerAnimation::Death;
 
-        game_controller.update_highscore(pkv);
-
         if keyboard_input.just_pressed(KeyCode::Space)
             // || keyboard_input.just_pressed(KeyCode::Escape)
-            || game_controller.game_state == GameState::Restart
+            || restart
         {
-            // reset game
-            game_controller.reset_game(
-                &mut commands,
-                &mut player,
-                &mut transform,
-                &mut pipes_query,
-                &pipes_handler,
-            );
+            if !restart && game_controller.checkpoint.is_some() {
+                // respawn at the practice checkpoint
+                game_controller.respawn_at_checkpoint(
+                    &mut commands,
+                    &mut player,
+                    &mut transform,
+                    &mut pipes_query,
+                    &pipes_handler,
+                );
+                reset_events.send(GameReset { respawned: true });
+            } else {
+                // reset game
+                game_controller.reset_game(
+                    &mut commands,
+                    &mut player,
+                    &mut transform,
+                    &mut pipes_query,
+                    &pipes_handler,
+                );
+                reset_events.send(GameReset { respawned: false });
+            }
+        }
+    } else if restart || (game_controller.won && keyboard_input.just_pressed(KeyCode::Space)) {
+        // restart requested while alive, e.g. after changing the game mode or winning a challenge
+        let paused = game_controller.is_game_paused();
+
+        game_controller.reset_game(
+            &mut commands,
+            &mut player,
+            &mut transform,
+            &mut pipes_query,
+            &pipes_handler,
+        );
+        reset_events.send(GameReset { respawned: false });
+
+        // changed in the settings, wait for them to be closed
+        if paused {
+            game_controller.pause_game(&mut pause_events);
         }
     }
 }
 
 pub struct PlayerHandler {
-    texture: Handle<Image>,
-    jump_sound: Handle<AudioSource>,
-    hit_sound: Handle<AudioSource>,
-    lose_sound: Handle<AudioSource>,
+    pub texture: Handle<Image>, // swapped for the skin and theme once they are loaded
 }
 
 impl FromWorld for PlayerHandler {
@@ -171,10 +199,7 @@ impl FromWorld for PlayerHandler {
         let asset_server = world.get_resource::<AssetServer>().unwrap();
 
         PlayerHandler {
-            texture: asset_server.load("sprites/bird.png"),
-            jump_sound: asset_server.load("sounds/jump.wav"),
-            hit_sound: asset_server.load("sounds/hit.wav"),
-            lose_sound: asset_server.load("sounds/lose.wav"),
+            texture: asset_server.load(CLASSIC_BIRD_PATH),
         }
     }
 }
@@ -183,9 +208,8 @@ impl FromWorld for PlayerHandler {
 pub struct Player {
     pub delta_y: f32,
     pub dead: bool,
-    hit_sound: bool,
-    lose_sound: bool,
     animation: PlayerAnimation,
+    pub idle_y: f32, // height the idle animation hovers around
 }
 
 enum PlayerAnimation {
@@ -198,13 +222,20 @@ enum PlayerAnimation {
 impl Player {
     pub fn die(&mut self, player_transform: &mut Transform) {
         self.delta_y = 0.0;
+        self.idle_y = PLAYER_START_Y;
         player_transform.translation.y = PLAYER_START_Y;
         player_transform.rotation.z = 0.0;
         player_transform.rotation.w = 1.0;
 
         self.dead = false;
-        self.hit_sound = false;
-        self.lose_sound = false;
+    }
+
+    /// Kill the bird, returns false if it was already dead
+    pub fn hit(&mut self) -> bool {
+        let was_alive = !self.dead;
+        self.dead = true;
+
+        was_alive
     }
 }
 
@@ -0,0 +1,344 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+
+use crate::events::GameReset;
+use crate::particles;
+use crate::sound::{EffectsChannel, EffectsHandler, SoundEffect};
+use crate::ui::{HudText, UiZ};
+use crate::*;
+
+const POWER_UP_PARTICLES: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUpKind {
+    Shield,
+    SlowTime,
+    Magnet,
+    Shrink,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 4] = [
+        PowerUpKind::Shield,
+        PowerUpKind::SlowTime,
+        PowerUpKind::Magnet,
+        PowerUpKind::Shrink,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerUpKind::Shield => "Shield",
+            PowerUpKind::SlowTime => "Slow Time",
+            PowerUpKind::Magnet => "Magnet",
+            PowerUpKind::Shrink => "Shrink",
+        }
+    }
+
+    pub fn texture_path(&self) -> &'static str {
+        match self {
+            PowerUpKind::Shield => "sprites/powerups/shield.png",
+            PowerUpKind::SlowTime => "sprites/powerups/slow.png",
+            PowerUpKind::Magnet => "sprites/powerups/magnet.png",
+            PowerUpKind::Shrink => "sprites/powerups/shrink.png",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PowerUpKind::Shield => Color::rgb(0.24, 0.55, 0.94),
+            PowerUpKind::SlowTime => Color::rgb(0.59, 0.35, 0.86),
+            PowerUpKind::Magnet => Color::rgb(0.88, 0.24, 0.24),
+            PowerUpKind::Shrink => Color::rgb(0.24, 0.75, 0.35),
+        }
+    }
+
+    /// Give the effect to the player, picking it up again restarts the timer
+    fn apply(&self, player: &mut EntityCommands) {
+        match self {
+            PowerUpKind::Shield => player.insert(Shield {
+                remaining: SHIELD_SECS,
+                absorbed: false,
+            }),
+            PowerUpKind::SlowTime => player.insert(SlowTime {
+                remaining: SLOW_TIME_SECS,
+            }),
+            PowerUpKind::Magnet => player.insert(Magnet {
+                remaining: MAGNET_SECS,
+            }),
+            PowerUpKind::Shrink => player.insert(Shrink {
+                remaining: SHRINK_SECS,
+            }),
+        };
+    }
+}
+
+/// Power-up waiting in a pipe gap
+#[derive(Component)]
+pub struct PowerUpPickup {
+    pub kind: PowerUpKind,
+    pub collected: bool,
+}
+
+/// Timed effect on the player entity, removed once it runs out
+pub trait PowerUp: Component {
+    const KIND: PowerUpKind;
+
+    fn remaining(&self) -> f32;
+    fn remaining_mut(&mut self) -> &mut f32;
+}
+
+/// Absorbs one pipe hit, then lets the bird leave the pipe before it breaks
+#[derive(Component)]
+pub struct Shield {
+    remaining: f32,
+    absorbed: bool,
+}
+
+impl Shield {
+    pub fn absorb(&mut self) {
+        if !self.absorbed {
+            self.absorbed = true;
+            self.remaining = SHIELD_GRACE_SECS;
+        }
+    }
+}
+
+/// Scales down the speed multiplier of the pipes
+#[derive(Component)]
+pub struct SlowTime {
+    remaining: f32,
+}
+
+/// Pulls nearby coins towards the bird
+#[derive(Component)]
+pub struct Magnet {
+    remaining: f32,
+}
+
+/// Makes the bird and its hitbox smaller
+#[derive(Component)]
+pub struct Shrink {
+    remaining: f32,
+}
+
+macro_rules! impl_power_up {
+    ($component:ty, $kind:expr) => {
+        impl PowerUp for $component {
+            const KIND: PowerUpKind = $kind;
+
+            fn remaining(&self) -> f32 {
+                self.remaining
+            }
+
+            fn remaining_mut(&mut self) -> &mut f32 {
+                &mut self.remaining
+            }
+        }
+    };
+}
+
+impl_power_up!(Shield, PowerUpKind::Shield);
+impl_power_up!(SlowTime, PowerUpKind::SlowTime);
+impl_power_up!(Magnet, PowerUpKind::Magnet);
+impl_power_up!(Shrink, PowerUpKind::Shrink);
+
+fn power_up_pickup_system(
+    mut commands: Commands,
+    mut pickup_query: Query<(&mut PowerUpPickup, &mut Visibility, &GlobalTransform)>,
+    player_query: Query<(Entity, &Player, &Transform)>,
+    mut game_controller: ResMut<GameController>,
+    effects_handler: Res<EffectsHandler>,
+    effects: Res<AudioChannel<EffectsChannel>>,
+) {
+    if !game_controller.is_game_running() {
+        return;
+    }
+
+    let (player_entity, player, player_transform) = player_query.single();
+    if player.dead {
+        return;
+    }
+    let player_position = player_transform.translation.truncate();
+
+    for (mut pickup, mut visibility, transform) in pickup_query.iter_mut() {
+        if pickup.collected {
+            continue;
+        }
+
+        let position = transform.translation().truncate();
+        if position.distance(player_position) < POWER_UP_PICKUP_RADIUS {
+            pickup.collected = true;
+            visibility.is_visible = false;
+
+            pickup.kind.apply(&mut commands.entity(player_entity));
+
+            let game_controller = &mut *game_controller;
+            effects_handler.play(
+                &effects,
+                SoundEffect::PowerUp,
+                &game_controller.settings,
+                &mut game_controller.sound_rng,
+            );
+
+            particles::spawn_burst(
+                &mut commands,
+                position,
+                pickup.kind.color(),
+                POWER_UP_PARTICLES,
+            );
+        }
+    }
+}
+
+fn power_up_timer_system<T: PowerUp>(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut T), With<Player>>,
+    game_controller: Res<GameController>,
+    time: Res<Time>,
+) {
+    if !game_controller.is_game_running() {
+        return;
+    }
+
+    let delta_time = game_controller.delta_time(&time);
+
+    for (entity, mut power_up) in query.iter_mut() {
+        *power_up.remaining_mut() -= delta_time;
+
+        if power_up.remaining() <= 0.0 {
+            commands.entity(entity).remove::<T>();
+        }
+    }
+}
+
+/// A new run, or a practice respawn, starts without power-ups
+fn clear_power_ups_system(
+    mut commands: Commands,
+    query: Query<
+        Entity,
+        (
+            With<Player>,
+            Or<(With<Shield>, With<SlowTime>, With<Magnet>, With<Shrink>)>,
+        ),
+    >,
+    mut reset_events: EventReader<GameReset>,
+) {
+    if reset_events.iter().count() == 0 {
+        return;
+    }
+
+    for entity in query.iter() {
+        commands
+            .entity(entity)
+            .remove::<Shield>()
+            .remove::<SlowTime>()
+            .remove::<Magnet>()
+            .remove::<Shrink>();
+    }
+}
+
+fn power_up_visuals_system(
+    mut query: Query<
+        (
+            &mut Transform,
+            &mut Sprite,
+            Option<&Shield>,
+            Option<&Shrink>,
+        ),
+        With<Player>,
+    >,
+) {
+    for (mut transform, mut sprite, shield, shrink) in query.iter_mut() {
+        let scale = shrink.map_or(1.0, |_| SHRINK_SCALE);
+        transform.scale = Vec3::new(scale, scale, 1.0);
+
+        sprite.color = match shield {
+            // blink while the broken shield wears off
+            Some(shield) if shield.absorbed && (shield.remaining * 10.0) as i32 % 2 == 0 => {
+                Color::WHITE
+            }
+            Some(_) => Color::rgb(0.7, 0.85, 1.0),
+            None => Color::WHITE,
+        };
+    }
+}
+
+#[derive(Component)]
+struct PowerUpsText;
+
+fn power_ups_ui_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load(FONT_PATH),
+                    font_size: 30.0,
+                    color: Color::BLACK,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                // below the fps, the top right corner shows the versus scores
+                position: UiRect {
+                    top: Val::Px(40.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..default()
+            }),
+        )
+        .insert(PowerUpsText)
+        .insert(HudText)
+        .insert(UiZ(20.0));
+}
+
+fn timer_line<T: PowerUp>(power_up: Option<&T>) -> Option<String> {
+    power_up.map(|power_up| format!("{} {:.1}s", T::KIND.name(), power_up.remaining()))
+}
+
+fn power_ups_ui_system(
+    mut text_query: Query<&mut Text, With<PowerUpsText>>,
+    player_query: Query<
+        (
+            Option<&Shield>,
+            Option<&SlowTime>,
+            Option<&Magnet>,
+            Option<&Shrink>,
+        ),
+        With<Player>,
+    >,
+) {
+    let (shield, slow_time, magnet, shrink) = player_query.single();
+
+    let lines: Vec<String> = [
+        timer_line(shield),
+        timer_line(slow_time),
+        timer_line(magnet),
+        timer_line(shrink),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+pub struct PowerUpsPlugin;
+
+impl Plugin for PowerUpsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(power_ups_ui_setup)
+            .add_system(power_up_pickup_system.after(pipes_system))
+            .add_system(power_up_timer_system::<Shield>)
+            .add_system(power_up_timer_system::<SlowTime>)
+            .add_system(power_up_timer_system::<Magnet>)
+            .add_system(power_up_timer_system::<Shrink>)
+            .add_system(clear_power_ups_system)
+            .add_system(power_up_visuals_system)
+            .add_system(power_ups_ui_system);
+    }
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+
+use crate::ui::{HudText, UiZ};
+use crate::*;
+
+static TIME_SCALES: [f32; 3] = [1.0, 0.75, 0.5];
+
+#[derive(Component)]
+struct PracticeText;
+
+fn practice_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load(FONT_PATH),
+                    font_size: 25.0,
+                    color: Color::BLACK,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(5.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..default()
+            }),
+        )
+        .insert(PracticeText)
+        .insert(HudText)
+        .insert(UiZ(20.0));
+}
+
+fn practice_system(
+    mut game_controller: ResMut<GameController>,
+    player_query: Query<(&Player, &Transform)>,
+    pipes_query: Query<(&PipeParent, &Transform)>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    if game_controller.mode != GameMode::Practice || game_controller.is_game_paused() {
+        return;
+    }
+
+    // cycle the time scale
+    if keyboard_input.just_pressed(KeyCode::T) {
+        let i = TIME_SCALES
+            .iter()
+            .position(|scale| *scale == game_controller.time_scale)
+            .unwrap_or(0);
+        game_controller.time_scale = TIME_SCALES[(i + 1) % TIME_SCALES.len()];
+    }
+
+    // save a checkpoint
+    let (player, player_transform) = player_query.single();
+    if keyboard_input.just_pressed(KeyCode::C) && game_controller.is_game_running() && !player.dead
+    {
+        game_controller.checkpoint = Some(Checkpoint {
+            score: game_controller.score,
+            speed_multiplier: game_controller.speed_multiplier,
+            run_time: game_controller.run_time,
+            player_y: player_transform.translation.y,
+            pipes: pipes_query
+                .iter()
+                .map(|(pipe, transform)| pipe.snapshot(transform))
+                .collect(),
+            rng: game_controller.rng.clone(),
+            patterns: game_controller.patterns.clone(),
+        });
+    }
+}
+
+fn practice_ui_system(
+    mut text_query: Query<(&mut Text, &mut Visibility), With<PracticeText>>,
+    game_controller: Res<GameController>,
+) {
+    let (mut text, mut visibility) = text_query.single_mut();
+
+    visibility.is_visible = game_controller.mode == GameMode::Practice;
+
+    text.sections[0].value = format!(
+        "Practice - Speed x{} [T] - {} [C]",
+        game_controller.time_scale,
+        match &game_controller.checkpoint {
+            Some(checkpoint) => format!("Checkpoint at {}", checkpoint.score),
+            None => "No checkpoint".to_string(),
+        }
+    );
+}
+
+pub struct PracticePlugin;
+
+impl Plugin for PracticePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(practice_setup)
+            .add_system(practice_system)
+            .add_system(practice_ui_system);
+    }
+}
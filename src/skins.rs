@@ -0,0 +1,286 @@
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::network::Ghost;
+use crate::particles;
+use crate::themes::{Theme, ThemesHandler};
+use crate::*;
+
+/// Every skin that can be bought, loaded from `assets/skins/*.skins.ron`
+#[derive(Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "0b9c6a53-3f0e-4d7e-a3a1-6e2f8c4d1b27"]
+pub struct SkinCatalog {
+    pub skins: Vec<Skin>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Skin {
+    pub id: String,
+    pub name: String,
+    pub price: u32, // in coins, free skins are owned from the start
+    #[serde(default)]
+    pub achievement: Option<String>, // earned with the achievement instead of bought
+    pub look: SkinLook,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub enum SkinLook {
+    Bird(String),
+    Pipes { body: String, end: String },
+    Trail(Option<[f32; 3]>),
+    ThemeBird, // the bird of the active theme
+    ThemePipes,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkinSlot {
+    Bird,
+    Pipes,
+    Trail,
+}
+
+impl SkinSlot {
+    pub const ALL: [SkinSlot; 3] = [SkinSlot::Bird, SkinSlot::Pipes, SkinSlot::Trail];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SkinSlot::Bird => "Bird",
+            SkinSlot::Pipes => "Pipes",
+            SkinSlot::Trail => "Trail",
+        }
+    }
+}
+
+impl Skin {
+    pub fn slot(&self) -> SkinSlot {
+        match self.look {
+            SkinLook::Bird(_) | SkinLook::ThemeBird => SkinSlot::Bird,
+            SkinLook::Pipes { .. } | SkinLook::ThemePipes => SkinSlot::Pipes,
+            SkinLook::Trail(_) => SkinSlot::Trail,
+        }
+    }
+}
+
+impl SkinLook {
+    /// The look with the textures of `theme` filled in, the classic ones until it is loaded
+    fn themed(&self, theme: Option<&Theme>) -> SkinLook {
+        match (self, theme) {
+            (SkinLook::ThemeBird, Some(theme)) => SkinLook::Bird(theme.bird.clone()),
+            (SkinLook::ThemeBird, None) => SkinLook::Bird(CLASSIC_BIRD_PATH.to_string()),
+            (SkinLook::ThemePipes, Some(theme)) => SkinLook::Pipes {
+                body: theme.pipe_body.clone(),
+                end: theme.pipe_end.clone(),
+            },
+            (SkinLook::ThemePipes, None) => SkinLook::Pipes {
+                body: CLASSIC_PIPE_BODY_PATH.to_string(),
+                end: CLASSIC_PIPE_END_PATH.to_string(),
+            },
+            (look, _) => look.clone(),
+        }
+    }
+}
+
+impl SkinCatalog {
+    pub fn slot_skins(&self, slot: SkinSlot) -> Vec<&Skin> {
+        self.skins
+            .iter()
+            .filter(|skin| skin.slot() == slot)
+            .collect()
+    }
+
+    /// The selected skin of the slot, falling back to its first one
+    pub fn selected(&self, cosmetics: &Cosmetics, slot: SkinSlot) -> Option<&Skin> {
+        let skins = self.slot_skins(slot);
+
+        cosmetics
+            .selected(slot)
+            .and_then(|id| skins.iter().find(|skin| skin.id == *id))
+            .or_else(|| skins.first())
+            .copied()
+    }
+}
+
+/// Unlocked and selected skins, saved with the player statistics
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct Cosmetics {
+    pub unlocked: BTreeSet<String>, // skins bought with coins
+    pub bird: Option<String>,
+    pub pipes: Option<String>,
+    pub trail: Option<String>,
+}
+
+impl Cosmetics {
+    pub fn owns(&self, skin: &Skin, achievements: &BTreeSet<String>) -> bool {
+        match &skin.achievement {
+            Some(achievement) => achievements.contains(achievement),
+            None => skin.price == 0 || self.unlocked.contains(&skin.id),
+        }
+    }
+
+    pub fn selected(&self, slot: SkinSlot) -> Option<&String> {
+        match slot {
+            SkinSlot::Bird => self.bird.as_ref(),
+            SkinSlot::Pipes => self.pipes.as_ref(),
+            SkinSlot::Trail => self.trail.as_ref(),
+        }
+    }
+
+    pub fn select(&mut self, skin: &Skin) {
+        let id = Some(skin.id.clone());
+
+        match skin.slot() {
+            SkinSlot::Bird => self.bird = id,
+            SkinSlot::Pipes => self.pipes = id,
+            SkinSlot::Trail => self.trail = id,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SkinCatalogLoader;
+
+impl AssetLoader for SkinCatalogLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let catalog: SkinCatalog = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(catalog));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["skins.ron"]
+    }
+}
+
+pub struct SkinsHandler {
+    pub catalog: Handle<SkinCatalog>,
+}
+
+impl FromWorld for SkinsHandler {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
+
+        SkinsHandler {
+            catalog: asset_server.load(SKIN_CATALOG_PATH),
+        }
+    }
+}
+
+/// Trail colour of the selected skin, `None` leaves no trail
+#[derive(Default)]
+struct ActiveTrail(Option<Color>);
+
+/// Swap the bird, ghost and pipe textures whenever the selection, the catalog or the theme changes
+fn skins_apply_system(
+    mut player_query: Query<&mut Handle<Image>, Or<(With<Player>, With<Ghost>)>>,
+    mut blocks_query: Query<(&mut Handle<Image>, &PipeBlock), (Without<Player>, Without<Ghost>)>,
+    mut applied: Local<Vec<SkinLook>>,
+    mut active_trail: ResMut<ActiveTrail>,
+    (game_controller, skins_handler, catalogs, asset_server): (
+        Res<GameController>,
+        Res<SkinsHandler>,
+        Res<Assets<SkinCatalog>>,
+        Res<AssetServer>,
+    ),
+    (mut player_handler, mut pipes_handler): (ResMut<PlayerHandler>, ResMut<PipesHandler>),
+    (themes_handler, themes): (Res<ThemesHandler>, Res<Assets<Theme>>),
+) {
+    let catalog = match catalogs.get(&skins_handler.catalog) {
+        Some(catalog) => catalog,
+        None => return,
+    };
+    let theme = themes_handler.active(&themes, &game_controller.settings);
+
+    let cosmetics = &game_controller.player_stats.cosmetics;
+    let looks: Vec<SkinLook> = SkinSlot::ALL
+        .iter()
+        .filter_map(|slot| catalog.selected(cosmetics, *slot))
+        .map(|skin| skin.look.themed(theme))
+        .collect();
+
+    if *applied == looks {
+        return;
+    }
+    *applied = looks.clone();
+
+    for look in looks {
+        match look {
+            SkinLook::Bird(texture) => {
+                player_handler.texture = asset_server.load(texture.as_str());
+
+                for mut player_texture in player_query.iter_mut() {
+                    *player_texture = player_handler.texture.clone();
+                }
+            }
+            SkinLook::Pipes { body, end } => {
+                pipes_handler.texture_body = asset_server.load(body.as_str());
+                pipes_handler.texture_end = asset_server.load(end.as_str());
+
+                // the pipes are not rebuilt, so restyle their blocks in place
+                for (mut texture, block) in blocks_query.iter_mut() {
+                    *texture = match block.end {
+                        true => pipes_handler.texture_end.clone(),
+                        false => pipes_handler.texture_body.clone(),
+                    };
+                }
+            }
+            SkinLook::Trail(color) => {
+                active_trail.0 = color.map(|[r, g, b]| Color::rgb(r, g, b));
+            }
+            SkinLook::ThemeBird | SkinLook::ThemePipes => {} // filled in by `themed`
+        }
+    }
+}
+
+fn trail_system(
+    mut commands: Commands,
+    player_query: Query<(&Player, &Transform)>,
+    active_trail: Res<ActiveTrail>,
+    game_controller: Res<GameController>,
+    time: Res<Time>,
+    mut since_last: Local<f32>,
+) {
+    let color = match active_trail.0 {
+        Some(color) => color,
+        None => return,
+    };
+    if !game_controller.is_game_running() {
+        return;
+    }
+
+    let (player, player_transform) = player_query.single();
+    if player.dead {
+        return;
+    }
+
+    *since_last += game_controller.delta_time(&time);
+    if *since_last >= TRAIL_INTERVAL {
+        *since_last = 0.0;
+
+        let position = player_transform.translation.truncate() - Vec2::new(SPRITE_SIZE / 3.0, 0.0);
+        particles::spawn_trail(&mut commands, position, color);
+    }
+}
+
+pub struct SkinsPlugin;
+
+impl Plugin for SkinsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<SkinCatalog>()
+            .init_asset_loader::<SkinCatalogLoader>()
+            .init_resource::<SkinsHandler>()
+            .init_resource::<ActiveTrail>()
+            .add_system(skins_apply_system)
+            .add_system(trail_system);
+    }
+}
@@ -1,42 +1,262 @@
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
-use std::time::Duration;
+use rand::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
 
-use crate::GameController;
+use crate::events::{Flapped, NewHighScore, PlayerHit, PlayerLanded};
+use crate::*;
 
-fn start_background_audio(
-    asset_server: Res<AssetServer>,
-    audio: Res<Audio>,
+/// Kira channels the sounds are mixed on, one per bus
+pub struct MusicChannel;
+pub struct EffectsChannel;
+pub struct UiChannel;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AudioBus {
+    Master,
+    Music,
+    Effects,
+    Ui,
+}
+
+impl AudioBus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AudioBus::Master => "Master volume",
+            AudioBus::Music => "Music volume",
+            AudioBus::Effects => "Sound effects volume",
+            AudioBus::Ui => "Interface volume",
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SoundEffect {
+    Jump,
+    Hit,
+    Lose,
+    Score,
+    Coin,
+    PowerUp,
+    Click,
+}
+
+/// Samples of a sound effect, its gain on top of the bus volume and how much it varies
+struct SoundBankEntry {
+    effect: SoundEffect,
+    samples: &'static [&'static str], // one is picked at random each time
+    gain: f64,
+    pitch_variation: f64,  // the playback rate is 1 +- this
+    volume_variation: f64, // the gain is scaled by 1 +- this
+}
+
+static SOUND_BANK: [SoundBankEntry; 7] = [
+    SoundBankEntry {
+        effect: SoundEffect::Jump,
+        samples: &["sounds/jump.wav", "sounds/jump2.wav"],
+        gain: 0.5,
+        pitch_variation: 0.08,
+        volume_variation: 0.1,
+    },
+    SoundBankEntry {
+        effect: SoundEffect::Hit,
+        samples: &["sounds/hit.wav"],
+        gain: 1.0,
+        pitch_variation: 0.1,
+        volume_variation: 0.1,
+    },
+    SoundBankEntry {
+        effect: SoundEffect::Lose,
+        samples: &["sounds/lose.wav", "sounds/lose2.wav"],
+        gain: 2.0,
+        pitch_variation: 0.0,
+        volume_variation: 0.0,
+    },
+    SoundBankEntry {
+        effect: SoundEffect::Score,
+        samples: &["sounds/score.wav"],
+        gain: 1.0,
+        pitch_variation: 0.05,
+        volume_variation: 0.0,
+    },
+    SoundBankEntry {
+        effect: SoundEffect::Coin,
+        samples: &["sounds/coin.wav"],
+        gain: 1.0,
+        pitch_variation: 0.12,
+        volume_variation: 0.1,
+    },
+    SoundBankEntry {
+        effect: SoundEffect::PowerUp,
+        samples: &["sounds/powerup.wav"],
+        gain: 1.0,
+        pitch_variation: 0.0,
+        volume_variation: 0.0,
+    },
+    SoundBankEntry {
+        effect: SoundEffect::Click,
+        samples: &["sounds/click.wav"],
+        gain: 0.8,
+        pitch_variation: 0.05,
+        volume_variation: 0.0,
+    },
+];
+
+impl SoundEffect {
+    pub fn bus(&self) -> AudioBus {
+        match self {
+            SoundEffect::Click => AudioBus::Ui,
+            _ => AudioBus::Effects,
+        }
+    }
+}
+
+pub struct EffectsHandler {
+    sounds: HashMap<SoundEffect, (&'static SoundBankEntry, Vec<Handle<AudioSource>>)>,
+}
+
+impl FromWorld for EffectsHandler {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
+
+        let mut effects_handler = EffectsHandler {
+            sounds: HashMap::new(),
+        };
+        effects_handler.load_samples(asset_server, &HashMap::new());
+        effects_handler
+    }
+}
+
+impl EffectsHandler {
+    /// Load the samples of every effect, `overrides` replaces those of the sound bank
+    pub fn load_samples(
+        &mut self,
+        asset_server: &AssetServer,
+        overrides: &HashMap<SoundEffect, Vec<String>>,
+    ) {
+        self.sounds = SOUND_BANK
+            .iter()
+            .map(|entry| {
+                let samples = match overrides.get(&entry.effect) {
+                    Some(paths) if !paths.is_empty() => paths
+                        .iter()
+                        .map(|path| asset_server.load(path.as_str()))
+                        .collect(),
+                    _ => entry
+                        .samples
+                        .iter()
+                        .map(|path| asset_server.load(*path))
+                        .collect(),
+                };
+
+                (entry.effect, (entry, samples))
+            })
+            .collect();
+    }
+
+    /// Play a variation of `effect` on the channel of its bus
+    pub fn play<T: Resource>(
+        &self,
+        channel: &AudioChannel<T>,
+        effect: SoundEffect,
+        settings: &GameSettings,
+        rng: &mut impl Rng,
+    ) {
+        let (entry, samples) = &self.sounds[&effect];
+
+        // always draw all three, so the rng advances the same for every effect
+        let sample = rng.gen_range(0..samples.len());
+        let pitch = rng.gen_range(-1.0..=1.0) * entry.pitch_variation;
+        let volume = rng.gen_range(-1.0..=1.0) * entry.volume_variation;
+
+        channel
+            .play(samples[sample].clone())
+            .with_volume(settings.output_volume(effect.bus()) * entry.gain * (1.0 + volume))
+            .with_playback_rate(1.0 + pitch);
+    }
+}
+
+// sound effects for the gameplay events
+fn effects_audio_system(
+    mut flap_events: EventReader<Flapped>,
+    mut hit_events: EventReader<PlayerHit>,
+    mut landed_events: EventReader<PlayerLanded>,
+    mut high_score_events: EventReader<NewHighScore>,
+    effects: Res<AudioChannel<EffectsChannel>>,
+    effects_handler: Res<EffectsHandler>,
+    mut game_controller: ResMut<GameController>,
+) {
+    // borrow the settings and the sound rng apart
+    let GameController {
+        settings,
+        sound_rng,
+        ..
+    } = &mut *game_controller;
+
+    for _ in flap_events.iter() {
+        effects_handler.play(&effects, SoundEffect::Jump, settings, sound_rng);
+    }
+    for _ in hit_events.iter() {
+        effects_handler.play(&effects, SoundEffect::Hit, settings, sound_rng);
+    }
+    for _ in landed_events.iter() {
+        effects_handler.play(&effects, SoundEffect::Lose, settings, sound_rng);
+    }
+    for _ in high_score_events.iter() {
+        effects_handler.play(&effects, SoundEffect::Score, settings, sound_rng);
+    }
+}
+
+// click sound for every button
+fn ui_audio_system(
+    buttons_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    ui: Res<AudioChannel<UiChannel>>,
+    effects_handler: Res<EffectsHandler>,
     game_controller: Res<GameController>,
 ) {
-    audio
-        .play(asset_server.load("sounds/bg.wav"))
-        .fade_in(AudioTween::new(
-            Duration::from_secs(1),
-            AudioEasing::OutPowi(2),
-        ))
-        .with_volume(game_controller.settings.music_vol_level as f64)
-        .looped();
-}
-
-// pause background music when game is paused
-// fn background_audio_system(
-//     audio: Res<Audio>,
-//     game_controller: Res<GameController>,
-// ) {
-//     if game_controller.paused && audio.is_playing_sound() {
-//         audio.pause();
-//     } else if !game_controller.paused && !audio.is_playing_sound() {
-//         audio.resume();
-//     }
-// }
+    for interaction in buttons_query.iter() {
+        if interaction == &Interaction::Clicked {
+            // menus are not part of a run, so they do not use the seeded rng
+            effects_handler.play(
+                &ui,
+                SoundEffect::Click,
+                &game_controller.settings,
+                &mut thread_rng(),
+            );
+        }
+    }
+}
+
+// M mutes and unmutes everything
+fn mute_system(
+    mut game_controller: ResMut<GameController>,
+    mut pkv: ResMut<PkvStore>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    // the level editor uses M for moving pipes
+    if !keyboard_input.just_pressed(KeyCode::M) || game_controller.game_state == GameState::Editing
+    {
+        return;
+    }
+
+    game_controller.settings.muted = !game_controller.settings.muted;
+
+    pkv.set(GAME_SETTINGS_KEY, &game_controller.settings)
+        .expect("Failed to save game settings");
+}
 
 pub struct SoundPlugin;
 
 impl Plugin for SoundPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(AudioPlugin)
-            // .add_system(background_audio_system)
-            .add_startup_system(start_background_audio);
+            .add_audio_channel::<MusicChannel>()
+            .add_audio_channel::<EffectsChannel>()
+            .add_audio_channel::<UiChannel>()
+            .init_resource::<EffectsHandler>()
+            .add_system(effects_audio_system)
+            .add_system(ui_audio_system)
+            .add_system(mute_system);
     }
 }
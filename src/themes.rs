@@ -0,0 +1,144 @@
+use bevy::asset::{AssetLoader, LoadContext, LoadState, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::background::parallax::ParallaxHandler;
+use crate::background::sky::SkyPalette;
+use crate::sound::{EffectsHandler, SoundEffect};
+use crate::*;
+
+/// Sprites, colors and sounds swapped together, loaded from `assets/themes/*.theme.ron`
+#[derive(Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "7e2c94b1-58d3-4a6f-b0e9-3d1a6c8f5e42"]
+pub struct Theme {
+    pub name: String,
+    pub bird: String, // worn by the default bird skin
+    pub pipe_body: String,
+    pub pipe_end: String,
+    pub parallax: String, // the cloud and hill layers, a `*.parallax.ron` file
+    pub background_color: [f32; 3], // the sky at noon, in 0-255
+    #[serde(default)]
+    pub sky: Option<Vec<SkyPalette>>, // replaces the whole day, from dawn to midnight
+    #[serde(default)]
+    pub sounds: HashMap<SoundEffect, Vec<String>>, // samples replacing those of the sound bank
+}
+
+impl Theme {
+    /// Palettes of the day and night cycle
+    pub fn palettes(&self) -> Vec<SkyPalette> {
+        self.sky.clone().unwrap_or_else(|| {
+            let mut palettes = SKY_PALETTES.to_vec();
+            palettes[1].sky = self.background_color;
+            palettes
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let theme: Theme = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(theme));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+pub struct ThemesHandler {
+    pub themes: Vec<Handle<Theme>>, // in the order of `THEMES`
+}
+
+impl FromWorld for ThemesHandler {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
+
+        ThemesHandler {
+            themes: THEMES.iter().map(|path| asset_server.load(*path)).collect(),
+        }
+    }
+}
+
+impl ThemesHandler {
+    pub fn active_handle(&self, settings: &GameSettings) -> &Handle<Theme> {
+        &self.themes[settings.theme % self.themes.len()]
+    }
+
+    /// The theme picked in the settings, once it is loaded
+    pub fn active<'a>(
+        &self,
+        themes: &'a Assets<Theme>,
+        settings: &GameSettings,
+    ) -> Option<&'a Theme> {
+        themes.get(self.active_handle(settings))
+    }
+}
+
+/// Point the background and sound handlers at the active theme whenever it changes,
+/// the bird and pipes are restyled by the skins
+fn theme_apply_system(
+    mut events: EventReader<AssetEvent<Theme>>,
+    mut applied: Local<Option<Handle<Theme>>>,
+    mut parallax_handler: ResMut<ParallaxHandler>,
+    mut effects_handler: ResMut<EffectsHandler>,
+    (themes_handler, themes): (Res<ThemesHandler>, Res<Assets<Theme>>),
+    game_controller: Res<GameController>,
+    asset_server: Res<AssetServer>,
+) {
+    let handle = themes_handler.active_handle(&game_controller.settings);
+
+    let mut changed = applied.as_ref() != Some(handle);
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle: loaded } | AssetEvent::Modified { handle: loaded } => {
+                changed |= loaded == handle;
+            }
+            AssetEvent::Removed { .. } => {}
+        }
+    }
+
+    if !changed {
+        return;
+    }
+
+    // tried again every frame until the theme is loaded
+    match themes.get(handle) {
+        Some(theme) => {
+            parallax_handler.layers = asset_server.load(theme.parallax.as_str());
+            effects_handler.load_samples(&asset_server, &theme.sounds);
+        }
+        None if asset_server.get_load_state(handle) == LoadState::Failed => {
+            let path = THEMES[game_controller.settings.theme % THEMES.len()];
+            warn!("Could not load the theme {}, using the classic look", path);
+
+            parallax_handler.layers = asset_server.load(CLASSIC_PARALLAX_PATH);
+            effects_handler.load_samples(&asset_server, &HashMap::new());
+        }
+        None => return,
+    }
+    *applied = Some(handle.clone());
+}
+
+pub struct ThemesPlugin;
+
+impl Plugin for ThemesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Theme>()
+            .init_asset_loader::<ThemeLoader>()
+            .init_resource::<ThemesHandler>()
+            .add_system(theme_apply_system);
+    }
+}
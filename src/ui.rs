@@ -4,11 +4,14 @@ use bevy::prelude::*;
 use crate::ui::window::*;
 use crate::*;
 
+mod customize;
 mod game_over;
 mod settings;
+pub mod toast;
 mod window;
 
 static PRESS_START_TEXT: &str = "Press space to start";
+static WAITING_FOR_OPPONENT_TEXT: &str = "Waiting for opponent...";
 
 fn ui_setup(
     mut commands: Commands,
@@ -75,6 +78,7 @@ fn ui_setup(
                 .insert(UiText {
                     text_type: UiTextType::StartMessage,
                 })
+                .insert(HudText)
                 .insert(UiZ(20.0));
 
             // High score text
@@ -97,16 +101,29 @@ fn ui_setup(
                 .insert(UiText {
                     text_type: UiTextType::HighScore,
                 })
+                .insert(HudText)
                 .insert(UiZ(20.0));
 
             // Score text
             parent
                 .spawn_bundle(
-                    TextBundle::from_sections([TextSection::from_style(TextStyle {
-                        font: asset_server.load(FONT_PATH),
-                        font_size: 80.0,
-                        color: Color::BLACK,
-                    })])
+                    TextBundle::from_sections([
+                        TextSection::from_style(TextStyle {
+                            font: asset_server.load(FONT_PATH),
+                            font_size: 80.0,
+                            color: Color::BLACK,
+                        }),
+                        // coins collected this run
+                        TextSection::from_style(TextStyle {
+                            font: asset_server.load(FONT_PATH),
+                            font_size: 40.0,
+                            color: Color::rgb(
+                                COIN_COLOR[0] / 255.0,
+                                COIN_COLOR[1] / 255.0,
+                                COIN_COLOR[2] / 255.0,
+                            ),
+                        }),
+                    ])
                     .with_style(Style {
                         margin: UiRect {
                             top: Val::Percent(10.0),
@@ -123,6 +140,7 @@ fn ui_setup(
                 .insert(UiText {
                     text_type: UiTextType::Score,
                 })
+                .insert(HudText)
                 .insert(UiZ(20.0));
         });
 }
@@ -142,6 +160,12 @@ fn text_ui_system(
             UiTextType::StartMessage => {
                 if game_controller.was_game_waiting() {
                     visibility.is_visible = true;
+
+                    text.sections[0].value = if game_controller.is_connecting() {
+                        WAITING_FOR_OPPONENT_TEXT.to_string()
+                    } else {
+                        PRESS_START_TEXT.to_string()
+                    };
                 } else {
                     visibility.is_visible = false;
                 }
@@ -152,27 +176,49 @@ fn text_ui_system(
                 } else if game_controller.was_game_waiting() {
                     visibility.is_visible = true;
 
-                    text.sections[0].value = format!(
-                        "{}: {}",
-                        HIGH_SCORE_TEXT, game_controller.player_stats.high_score
-                    );
+                    text.sections[0].value = match game_controller.mode {
+                        GameMode::Daily => game_controller.daily_status(),
+                        GameMode::TimeAttack | GameMode::PipeRush => {
+                            game_controller.challenge_status()
+                        }
+                        GameMode::Levels => game_controller.level_status(),
+                        GameMode::Editor => "Test run".to_string(),
+                        _ => format!(
+                            "{}: {}",
+                            HIGH_SCORE_TEXT, game_controller.player_stats.high_score
+                        ),
+                    };
+                    text.sections[1].value = String::new();
                 } else {
                     text.sections[0].value = game_controller.score.to_string();
+                    text.sections[1].value = if game_controller.coins > 0 {
+                        format!("  {} coins", game_controller.coins)
+                    } else {
+                        String::new()
+                    };
                     visibility.is_visible = true;
                 }
             }
             UiTextType::HighScore => {
                 if game_controller.is_game_finished(player_transform)
-                    || game_controller.game_state == GameState::Waiting
+                    || game_controller.was_game_waiting()
                 {
                     visibility.is_visible = false;
                 } else if game_controller.has_game_started() {
+                    // hidden in modes without a score to beat
+                    let target_score = match game_controller.target_score() {
+                        Some(target_score) => target_score,
+                        None => {
+                            visibility.is_visible = false;
+                            continue;
+                        }
+                    };
+
                     visibility.is_visible = true;
 
-                    let value = if game_controller.score <= game_controller.player_stats.high_score
-                    {
-                        game_controller.player_stats.high_score.to_string()
-                    } else if game_controller.score - 1 == game_controller.player_stats.high_score {
+                    let value = if game_controller.score <= target_score {
+                        target_score.to_string()
+                    } else if game_controller.score - 1 == target_score {
                         "New High Score!".to_string()
                     } else {
                         "".to_string()
@@ -219,10 +265,16 @@ impl Plugin for UIPlugin {
             .add_startup_system(ui_setup)
             .add_system(text_ui_system)
             .add_plugin(settings::SettingsPlugin)
+            .add_plugin(customize::CustomizePlugin)
+            .add_plugin(toast::ToastPlugin)
             .add_plugin(game_over::GameOverUiPlugin);
     }
 }
 
+/// Text drawn straight over the sky, its first section is recolored to stay readable on it
+#[derive(Component)]
+pub struct HudText;
+
 pub struct UiZPlugin; // TODO: fix bevy ui
 #[derive(Component)]
 pub struct UiZ(pub f32);
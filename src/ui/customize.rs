@@ -0,0 +1,359 @@
+use bevy::prelude::*;
+
+use crate::events::{GamePaused, GameResumed, RestartRequested};
+use crate::skins::{SkinCatalog, SkinSlot, SkinsHandler};
+use crate::ui::*;
+
+fn customize_ui_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_style = TextStyle {
+        font: asset_server.load(FONT_PATH),
+        font_size: 30.0,
+        color: Color::WHITE,
+    };
+
+    let window = UiWindow::new();
+    window.with_width_percent(0.6).spawn_with_children(
+        &mut commands,
+        |parent| {
+            SectionHeader::from_title(
+                parent,
+                "Customize",
+                TextStyle {
+                    font_size: 50.0,
+                    ..text_style.clone()
+                },
+            );
+
+            // coin balance
+            SectionHeader::from_title_with(
+                parent,
+                "",
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::GOLD,
+                    ..text_style.clone()
+                },
+                (CustomizeText {
+                    slot: None,
+                    text_type: CustomizeTextType::Balance,
+                },),
+            );
+
+            for slot in SkinSlot::ALL {
+                spawn_slot_row(parent, &text_style, slot);
+            }
+
+            // close button
+            spawn_button(
+                parent,
+                &text_style,
+                "x",
+                Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(0.0),
+                        right: Val::Px(0.0),
+                        ..Default::default()
+                    },
+                    ..button_style()
+                },
+                CustomizeButton {
+                    slot: None,
+                    action: CustomizeAction::Close,
+                },
+            );
+        },
+        CustomizeUi,
+    );
+}
+
+fn button_style() -> Style {
+    Style {
+        size: Size::new(Val::Auto, Val::Percent(100.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        padding: UiRect {
+            left: Val::Px(10.0),
+            right: Val::Px(10.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    text_style: &TextStyle,
+    text: &str,
+    style: Style,
+    button: CustomizeButton,
+) {
+    let slot = button.slot;
+    let is_choose = button.action == CustomizeAction::Choose;
+
+    parent
+        .spawn_bundle(ButtonBundle {
+            style,
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|button_parent| {
+            let mut button_text = button_parent.spawn_bundle(TextBundle {
+                text: Text::from_section(text, text_style.clone()),
+                ..Default::default()
+            });
+            button_text.insert(UiZ(34.0));
+
+            // the choose button tells what choosing the skin would do
+            if is_choose {
+                button_text.insert(CustomizeText {
+                    slot,
+                    text_type: CustomizeTextType::Action,
+                });
+            }
+        })
+        .insert(UiZ(33.0))
+        .insert(button);
+}
+
+/// Slot name, the browsed skin, browse buttons and the buy or use button
+fn spawn_slot_row(parent: &mut ChildBuilder, text_style: &TextStyle, slot: SkinSlot) {
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                justify_content: JustifyContent::FlexEnd,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Row,
+                margin: UiRect {
+                    top: Val::Percent(3.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|row| {
+            row.spawn_bundle(TextBundle {
+                text: Text::from_section(slot.name(), text_style.clone()),
+                style: Style {
+                    margin: UiRect {
+                        right: Val::Auto,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(UiZ(33.0));
+
+            row.spawn_bundle(TextBundle {
+                text: Text::from_section("", text_style.clone()),
+                ..Default::default()
+            })
+            .insert(UiZ(32.0))
+            .insert(CustomizeText {
+                slot: Some(slot),
+                text_type: CustomizeTextType::SkinName,
+            });
+
+            for (text, action) in [
+                ("<", CustomizeAction::Previous),
+                (">", CustomizeAction::Next),
+                ("", CustomizeAction::Choose),
+            ] {
+                spawn_button(
+                    row,
+                    text_style,
+                    text,
+                    button_style(),
+                    CustomizeButton {
+                        slot: Some(slot),
+                        action,
+                    },
+                );
+            }
+        })
+        .insert(UiZ(31.0));
+}
+
+fn customize_ui_system(
+    mut visibility_query: Query<&mut Visibility, With<CustomizeUi>>,
+    buttons_query: Query<(&Interaction, &CustomizeButton), Changed<Interaction>>,
+    mut text_query: Query<(&mut Text, &CustomizeText)>,
+    (mut browsed, mut was_visible): (Local<[usize; 3]>, Local<bool>),
+    (mut restart_events, mut pause_events, mut resume_events): (
+        EventWriter<RestartRequested>,
+        EventWriter<GamePaused>,
+        EventWriter<GameResumed>,
+    ),
+
+    (mut game_controller, keyboard_input, mut pkv, skins_handler, catalogs): (
+        ResMut<GameController>,
+        Res<Input<KeyCode>>,
+        ResMut<PkvStore>,
+        Res<SkinsHandler>,
+        Res<Assets<SkinCatalog>>,
+    ),
+) {
+    let mut visibility = visibility_query.single_mut();
+
+    let catalog = match catalogs.get(&skins_handler.catalog) {
+        Some(catalog) => catalog,
+        None => return,
+    };
+
+    // only opened between runs, it pauses the game like the settings
+    if keyboard_input.just_pressed(KeyCode::C)
+        && (visibility.is_visible || game_controller.was_game_waiting())
+    {
+        visibility.is_visible = !visibility.is_visible;
+
+        if visibility.is_visible {
+            if !game_controller.is_game_paused() {
+                game_controller.pause_game(&mut pause_events);
+            }
+        } else {
+            game_controller.resume_game(&mut resume_events, &mut restart_events);
+        }
+    }
+
+    // browse from the selected skins whenever the window opens
+    if visibility.is_visible && !*was_visible {
+        for (i, slot) in SkinSlot::ALL.iter().enumerate() {
+            let selected = catalog.selected(&game_controller.player_stats.cosmetics, *slot);
+            browsed[i] = catalog
+                .slot_skins(*slot)
+                .iter()
+                .position(|skin| selected.map_or(false, |selected| selected.id == skin.id))
+                .unwrap_or(0);
+        }
+    }
+    *was_visible = visibility.is_visible;
+
+    if !visibility.is_visible {
+        return;
+    }
+
+    for (interaction, button) in buttons_query.iter() {
+        if interaction == &Interaction::Clicked {
+            let slot = match button.slot {
+                Some(slot) => slot,
+                None => {
+                    visibility.is_visible = false;
+                    game_controller.resume_game(&mut resume_events, &mut restart_events);
+                    continue;
+                }
+            };
+            let index = SkinSlot::ALL.iter().position(|s| *s == slot).unwrap();
+            let skins = catalog.slot_skins(slot);
+            if skins.is_empty() {
+                continue;
+            }
+
+            match button.action {
+                CustomizeAction::Previous => {
+                    browsed[index] = (browsed[index] + skins.len() - 1) % skins.len();
+                }
+                CustomizeAction::Next => {
+                    browsed[index] = (browsed[index] + 1) % skins.len();
+                }
+                CustomizeAction::Choose => {
+                    let skin = skins[browsed[index] % skins.len()];
+                    let stats = &mut game_controller.player_stats;
+
+                    if !stats.cosmetics.owns(skin, &stats.achievements) {
+                        // achievement skins can not be bought
+                        if skin.achievement.is_some() || stats.coins < skin.price {
+                            continue;
+                        }
+                        stats.coins -= skin.price;
+                        stats.cosmetics.unlocked.insert(skin.id.clone());
+                    }
+                    stats.cosmetics.select(skin);
+
+                    game_controller.save_player_stats(&mut pkv);
+                }
+                CustomizeAction::Close => {}
+            }
+        }
+    }
+
+    let stats = &game_controller.player_stats;
+    for (mut text, customize_text) in text_query.iter_mut() {
+        let slot = match customize_text.slot {
+            Some(slot) => slot,
+            None => {
+                text.sections[0].value = format!("Coins: {}", stats.coins);
+                continue;
+            }
+        };
+        let index = SkinSlot::ALL.iter().position(|s| *s == slot).unwrap();
+        let skins = catalog.slot_skins(slot);
+        let skin = match skins.get(browsed[index]) {
+            Some(skin) => skin,
+            None => continue,
+        };
+
+        text.sections[0].value = match customize_text.text_type {
+            CustomizeTextType::SkinName => {
+                format!("{} ({}/{})", skin.name, browsed[index] + 1, skins.len())
+            }
+            CustomizeTextType::Action => {
+                let selected = catalog.selected(&stats.cosmetics, slot);
+
+                if selected.map_or(false, |selected| selected.id == skin.id) {
+                    "In use".to_string()
+                } else if stats.cosmetics.owns(skin, &stats.achievements) {
+                    "Use".to_string()
+                } else if skin.achievement.is_some() {
+                    "Achievement".to_string()
+                } else if stats.coins >= skin.price {
+                    format!("Buy {}", skin.price)
+                } else {
+                    format!("{} coins", skin.price)
+                }
+            }
+            CustomizeTextType::Balance => continue,
+        };
+    }
+}
+
+#[derive(Component)]
+pub struct CustomizeUi;
+
+#[derive(Component)]
+struct CustomizeButton {
+    slot: Option<SkinSlot>,
+    action: CustomizeAction,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CustomizeAction {
+    Previous,
+    Next,
+    Choose,
+    Close,
+}
+
+#[derive(Component)]
+struct CustomizeText {
+    slot: Option<SkinSlot>,
+    text_type: CustomizeTextType,
+}
+
+enum CustomizeTextType {
+    Balance,
+    SkinName,
+    Action,
+}
+
+pub struct CustomizePlugin;
+
+impl Plugin for CustomizePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(customize_ui_setup)
+            .add_system(customize_ui_system);
+    }
+}
@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 
+use crate::events::RestartRequested;
 use crate::ui::*;
 
 fn game_over_ui_setup(
@@ -32,7 +33,7 @@ fn game_over_ui_setup(
     window.with_width_percent(0.4f32).spawn_with_children(
         &mut commands,
         |parent| {
-            SectionHeader::from_title(
+            SectionHeader::from_title_with(
                 parent,
                 "Game Over",
                 TextStyle {
@@ -40,6 +41,9 @@ fn game_over_ui_setup(
                     font_size: 50.0,
                     color: Color::WHITE,
                 },
+                (WindowValueText {
+                    text_type: WindowValueType::Title,
+                },),
             );
 
             parent
@@ -75,6 +79,30 @@ fn game_over_ui_setup(
                     text_type: WindowValueType::HighScore,
                 });
 
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 25.0,
+                            ..text_style.clone()
+                        },
+                    ),
+                    style: Style {
+                        margin: UiRect {
+                            top: Val::Percent(4.0),
+                            ..Default::default()
+                        },
+                        align_self: AlignSelf::Center,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(UiZ(32.0))
+                .insert(WindowValueText {
+                    text_type: WindowValueType::Details,
+                });
+
             let retry_button = UiButton::new("retry_button");
             retry_button.spawn_from_text(
                 parent,
@@ -94,10 +122,11 @@ fn game_over_ui_setup(
 fn game_over_ui_system(
     mut visibility_query: Query<&mut Visibility, With<GameOverUi>>,
     mut text_query: Query<(&mut Text, &WindowValueText)>,
-    mut ui_button_query: Query<(&mut UiButton, &Interaction)>,
+    ui_button_query: Query<(&UiButton, &Interaction), Changed<Interaction>>,
     player_transform_query: Query<(&Transform, &Player)>,
 
     mut game_controller: ResMut<GameController>,
+    mut restart_events: EventWriter<RestartRequested>,
 ) {
     let mut visibility = visibility_query.single_mut();
 
@@ -108,24 +137,71 @@ fn game_over_ui_system(
 
         for (mut text, window_value) in text_query.iter_mut() {
             match window_value.text_type {
+                WindowValueType::Title => {
+                    text.sections[0].value = if game_controller.won
+                        && game_controller.mode.plays_level()
+                    {
+                        "Level Complete!".to_string()
+                    } else if game_controller.won {
+                        "Challenge Complete!".to_string()
+                    } else {
+                        "Game Over".to_string()
+                    };
+                }
                 WindowValueType::Score => {
-                    text.sections[0].value = format!("Score: {}", game_controller.score);
+                    text.sections[0].value = if game_controller.coins > 0 {
+                        format!(
+                            "Score: {} - Coins: {} ({} saved)",
+                            game_controller.score,
+                            game_controller.coins,
+                            game_controller.player_stats.coins
+                        )
+                    } else {
+                        format!("Score: {}", game_controller.score)
+                    };
                 }
                 WindowValueType::HighScore => {
-                    text.sections[0].value =
-                        format!("High Score: {}", game_controller.player_stats.high_score);
+                    text.sections[0].value = match game_controller.mode {
+                        GameMode::Daily => {
+                            format!("Daily Best: {}", game_controller.player_stats.daily_best)
+                        }
+                        GameMode::TimeAttack => format!(
+                            "Best: {:.1}s",
+                            game_controller.player_stats.time_attack_best
+                        ),
+                        GameMode::PipeRush => match game_controller.player_stats.pipe_rush_best {
+                            Some(best) => format!("Best: {:.1}s", best),
+                            None => "Best: -".to_string(),
+                        },
+                        GameMode::Levels => {
+                            format!("Best: {}/3 stars", game_controller.best_level_stars())
+                        }
+                        GameMode::Editor => "Test run".to_string(),
+                        _ => format!("High Score: {}", game_controller.player_stats.high_score),
+                    };
+                }
+                WindowValueType::Details => {
+                    text.sections[0].value = match game_controller.mode {
+                        GameMode::Daily => daily_summary(&game_controller),
+                        GameMode::TimeAttack | GameMode::PipeRush => {
+                            challenge_summary(&game_controller)
+                        }
+                        GameMode::Levels | GameMode::Editor if game_controller.won => format!(
+                            "Stars: {}/3 - {} flaps",
+                            game_controller.stars, game_controller.flaps
+                        ),
+                        _ => "".to_string(),
+                    };
                 }
             }
         }
 
         // ui buttons
-        for (mut button, interaction) in ui_button_query.iter_mut() {
-            if interaction == &Interaction::Clicked && button.just_clicked {
-                button.just_clicked = false;
-
+        for (button, interaction) in ui_button_query.iter() {
+            if interaction == &Interaction::Clicked {
                 match button.button_id.as_str() {
                     "retry_button" => {
-                        game_controller.game_state = GameState::Restart;
+                        restart_events.send(RestartRequested);
                         println!("Restarting game");
                     }
 
@@ -133,8 +209,6 @@ fn game_over_ui_system(
                         println!("Unknown button: {}", button.button_id);
                     }
                 }
-            } else if interaction != &Interaction::Clicked {
-                button.just_clicked = true;
             }
         }
     } else {
@@ -142,6 +216,48 @@ fn game_over_ui_system(
     }
 }
 
+fn daily_summary(game_controller: &GameController) -> String {
+    let stats = &game_controller.player_stats;
+    let today = game_controller.daily_score().unwrap_or(0);
+    let (prior_best, prior_days) = stats.daily_history_before(&game_controller.daily_date);
+
+    let run = if game_controller.daily_attempt {
+        "Daily attempt"
+    } else {
+        "Practice run"
+    };
+    let comparison = match prior_best {
+        None => "Your first daily challenge!".to_string(),
+        Some(best) if today > best => {
+            format!("Today: {} - beats {} prior days (best {})", today, prior_days, best)
+        }
+        Some(best) => format!("Today: {} - prior best {} over {} days", today, best, prior_days),
+    };
+
+    format!(
+        "{} - Seed {:016x}\n{}\nStreak: {} days",
+        run, game_controller.seed, comparison, stats.daily_streak
+    )
+}
+
+fn challenge_summary(game_controller: &GameController) -> String {
+    match (game_controller.mode, game_controller.won) {
+        (GameMode::PipeRush, true) => format!(
+            "Passed {} pipes in {:.1}s",
+            PIPE_RUSH_PIPES, game_controller.run_time
+        ),
+        (GameMode::PipeRush, false) => format!(
+            "Passed {} of {} pipes",
+            game_controller.score, PIPE_RUSH_PIPES
+        ),
+        (_, true) => format!("Survived all {:.0}s!", TIME_ATTACK_SECS),
+        (_, false) => format!(
+            "Survived {:.1}s of {:.0}s",
+            game_controller.run_time, TIME_ATTACK_SECS
+        ),
+    }
+}
+
 #[derive(Component)]
 pub struct GameOverUi;
 
@@ -151,8 +267,10 @@ struct WindowValueText {
 }
 
 enum WindowValueType {
+    Title,
     Score,
     HighScore,
+    Details,
 }
 
 pub struct GameOverUiPlugin;
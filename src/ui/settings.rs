@@ -1,7 +1,11 @@
 use bevy::prelude::*;
-use bevy_kira_audio::prelude::*;
 
+use crate::events::{GamePaused, GameResumed, RestartRequested};
+use crate::sound::AudioBus;
+use crate::themes::{Theme, ThemesHandler};
+use crate::ui::customize::CustomizeUi;
 use crate::ui::*;
+use crate::weather::Weather;
 
 fn settings_ui_setup(
     mut commands: Commands,
@@ -37,10 +41,10 @@ fn settings_ui_setup(
                 },
             );
 
-            // audio settings section
+            // game settings section
             SectionHeader::from_title(
                 parent,
-                "Audio",
+                "Game",
                 TextStyle {
                     font: asset_server.load(FONT_PATH),
                     font_size: 40.0,
@@ -48,7 +52,72 @@ fn settings_ui_setup(
                 },
             );
 
-            // music volume setting
+            // game mode setting
+            SettingsElement::create(
+                parent,
+                TextStyle {
+                    font: asset_server.load(FONT_PATH),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+                "Mode",
+                &[(SettingsButtonType::ModeNext, ">")],
+                Some(SettingValueType::Mode),
+            );
+
+            // level setting
+            SettingsElement::create(
+                parent,
+                TextStyle {
+                    font: asset_server.load(FONT_PATH),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+                "Level",
+                &[(SettingsButtonType::LevelNext, ">")],
+                Some(SettingValueType::Level),
+            );
+
+            // theme setting
+            SettingsElement::create(
+                parent,
+                TextStyle {
+                    font: asset_server.load(FONT_PATH),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+                "Theme",
+                &[(SettingsButtonType::ThemeNext, ">")],
+                Some(SettingValueType::Theme),
+            );
+
+            // day and night cycle setting
+            SettingsElement::create(
+                parent,
+                TextStyle {
+                    font: asset_server.load(FONT_PATH),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+                "Sky follows",
+                &[(SettingsButtonType::SkyNext, ">")],
+                Some(SettingValueType::Sky),
+            );
+
+            // weather setting
+            SettingsElement::create(
+                parent,
+                TextStyle {
+                    font: asset_server.load(FONT_PATH),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+                "Weather",
+                &[(SettingsButtonType::WeatherNext, ">")],
+                Some(SettingValueType::Weather),
+            );
+
+            // customize window
             SettingsElement::create(
                 parent,
                 TextStyle {
@@ -56,15 +125,13 @@ fn settings_ui_setup(
                     font_size: 30.0,
                     color: Color::WHITE,
                 },
-                "Music volume",
-                &[
-                    (SettingsButtonType::MusicVolumeMinus, "-"),
-                    (SettingsButtonType::MusicVolumePlus, "+"),
-                ],
-                Some(SettingValueType::MusicVolume),
+                "Customize",
+                &[(SettingsButtonType::Customize, "Open")],
+                None,
             );
 
-            // sound effect volume setting
+            // broadcast setting
+            #[cfg(not(target_arch = "wasm32"))]
             SettingsElement::create(
                 parent,
                 TextStyle {
@@ -72,14 +139,56 @@ fn settings_ui_setup(
                     font_size: 30.0,
                     color: Color::WHITE,
                 },
-                "Sound effects volume",
-                &[
-                    (SettingsButtonType::EffectsVolumeMinus, "-"),
-                    (SettingsButtonType::EffectsVolumePlus, "+"),
-                ],
-                Some(SettingValueType::EffectsVolume),
+                "Broadcast game",
+                &[(
+                    SettingsButtonType::Broadcast,
+                    match game_controller.settings.broadcast {
+                        true => "On",
+                        false => "Off",
+                    },
+                )],
+                None,
             );
 
+            // audio settings section
+            SectionHeader::from_title(
+                parent,
+                "Audio",
+                TextStyle {
+                    font: asset_server.load(FONT_PATH),
+                    font_size: 40.0,
+                    color: Color::WHITE,
+                },
+            );
+
+            // volume settings of the mixer buses, the master one can also be muted with M
+            for bus in [
+                AudioBus::Master,
+                AudioBus::Music,
+                AudioBus::Effects,
+                AudioBus::Ui,
+            ] {
+                let mut buttons = vec![
+                    (SettingsButtonType::VolumeDown(bus), "-"),
+                    (SettingsButtonType::VolumeUp(bus), "+"),
+                ];
+                if bus == AudioBus::Master {
+                    buttons.push((SettingsButtonType::Mute, "Mute"));
+                }
+
+                SettingsElement::create(
+                    parent,
+                    TextStyle {
+                        font: asset_server.load(FONT_PATH),
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                    },
+                    bus.name(),
+                    &buttons,
+                    Some(SettingValueType::Volume(bus)),
+                );
+            }
+
             // debug settings section
             SectionHeader::from_title(
                 parent,
@@ -156,7 +265,6 @@ fn settings_ui_setup(
                 })
                 .insert(UiZ(40.0))
                 .insert(SettingsButton {
-                    just_clicked: true,
                     button_type: SettingsButtonType::Close,
                 });
         },
@@ -169,74 +277,121 @@ struct SettingsUI;
 
 fn settings_ui_system(
     mut settings_visibility_query: Query<&mut Visibility, With<SettingsUI>>,
+    mut customize_visibility_query: Query<
+        &mut Visibility,
+        (With<CustomizeUi>, Without<SettingsUI>),
+    >,
 
-    mut settings_buttons_query: Query<(&Interaction, &Children, &mut SettingsButton)>,
+    settings_buttons_query: Query<(&Interaction, &Children, &SettingsButton), Changed<Interaction>>,
     mut text_query: Query<&mut Text, Without<SettingValueText>>, // todo: change this
     mut value_query: Query<(&mut Text, &SettingValueText)>,
 
-    (mut game_controller, keyboard_input, mut pkv, audio): (
+    (mut game_controller, keyboard_input, mut pkv): (
         ResMut<GameController>,
         Res<Input<KeyCode>>,
         ResMut<PkvStore>,
-        Res<Audio>,
     ),
+    (mut restart_events, mut pause_events, mut resume_events): (
+        EventWriter<RestartRequested>,
+        EventWriter<GamePaused>,
+        EventWriter<GameResumed>,
+    ),
+    (themes_handler, themes): (Res<ThemesHandler>, Res<Assets<Theme>>),
 ) {
     let mut settings_visibility = settings_visibility_query.single_mut();
 
     fn close_settings(
         mut settings_visibility: &mut Visibility,
         game_controller: &mut GameController,
+        restart_events: &mut EventWriter<RestartRequested>,
+        pause_events: &mut EventWriter<GamePaused>,
+        resume_events: &mut EventWriter<GameResumed>,
     ) {
         settings_visibility.is_visible = !settings_visibility.is_visible;
 
         if settings_visibility.is_visible {
-            game_controller.pause_game();
+            game_controller.pause_game(pause_events);
         } else {
-            game_controller.resume_game();
+            game_controller.resume_game(resume_events, restart_events);
         }
     }
 
+    // the customize window is closed on its own
+    let customizing = customize_visibility_query.single().is_visible;
+
     if keyboard_input.just_pressed(KeyCode::Escape)
         && game_controller.game_state != GameState::Finished
+        && !customizing
     {
-        close_settings(&mut settings_visibility, &mut game_controller);
+        close_settings(
+            &mut settings_visibility,
+            &mut game_controller,
+            &mut restart_events,
+            &mut pause_events,
+            &mut resume_events,
+        );
     }
 
     let mut changed = false;
 
-    for (interaction, children, mut button) in settings_buttons_query.iter_mut() {
-        if interaction == &Interaction::Clicked && button.just_clicked {
-            button.just_clicked = false;
+    for (interaction, children, button) in settings_buttons_query.iter() {
+        if interaction == &Interaction::Clicked {
             changed = true;
 
             match button.button_type {
-                SettingsButtonType::MusicVolumeMinus => {
-                    game_controller.settings.music_vol_level -= 0.05;
-
-                    if game_controller.settings.music_vol_level < 0.0 {
-                        game_controller.settings.music_vol_level = 0.0;
-                    }
+                SettingsButtonType::ModeNext => {
+                    let mode = game_controller.mode.next();
+                    game_controller.change_mode(mode, &mut restart_events);
                 }
-                SettingsButtonType::MusicVolumePlus => {
-                    game_controller.settings.music_vol_level += 0.05;
+                SettingsButtonType::LevelNext => {
+                    game_controller.level_index = (game_controller.level_index + 1) % LEVELS.len();
+                    // synced again once the new level is loaded
+                    game_controller.level = None;
 
-                    if game_controller.settings.music_vol_level > 1.0 {
-                        game_controller.settings.music_vol_level = 1.0;
+                    if game_controller.mode == GameMode::Levels {
+                        game_controller.change_mode(GameMode::Levels, &mut restart_events);
                     }
                 }
-                SettingsButtonType::EffectsVolumeMinus => {
-                    game_controller.settings.effects_vol_level -= 0.05;
+                SettingsButtonType::ThemeNext => {
+                    game_controller.settings.theme =
+                        (game_controller.settings.theme + 1) % THEMES.len();
+                }
+                SettingsButtonType::SkyNext => {
+                    game_controller.settings.sky_mode = game_controller.settings.sky_mode.next();
+                }
+                SettingsButtonType::WeatherNext => {
+                    let mode = game_controller.mode;
 
-                    if game_controller.settings.effects_vol_level < 0.0 {
-                        game_controller.settings.effects_vol_level = 0.0;
+                    if Weather::selectable(mode) {
+                        let choice = Weather::next_choice(game_controller.settings.weather(mode));
+                        game_controller.settings.set_weather(mode, choice);
                     }
                 }
-                SettingsButtonType::EffectsVolumePlus => {
-                    game_controller.settings.effects_vol_level += 0.05;
+                SettingsButtonType::Customize => {
+                    // the game stays paused until the customize window is closed
+                    settings_visibility.is_visible = false;
+                    customize_visibility_query.single_mut().is_visible = true;
+                }
+                SettingsButtonType::Broadcast => {
+                    game_controller.settings.broadcast = !game_controller.settings.broadcast;
 
-                    if game_controller.settings.effects_vol_level > 1.0 {
-                        game_controller.settings.effects_vol_level = 1.0;
-                    }
+                    // change button text
+                    text_query.get_mut(children[0]).unwrap().sections[0].value =
+                        match game_controller.settings.broadcast {
+                            true => "On".to_string(),
+                            false => "Off".to_string(),
+                        }
+                }
+                SettingsButtonType::VolumeDown(bus) => {
+                    let level = game_controller.settings.volume_level_mut(bus);
+                    *level = (*level - 0.05).max(0.0);
+                }
+                SettingsButtonType::VolumeUp(bus) => {
+                    let level = game_controller.settings.volume_level_mut(bus);
+                    *level = (*level + 0.05).min(1.0);
+                }
+                SettingsButtonType::Mute => {
+                    game_controller.settings.muted = !game_controller.settings.muted;
                 }
                 SettingsButtonType::FPSShow => {
                     game_controller.settings.show_fps = !game_controller.settings.show_fps;
@@ -249,34 +404,68 @@ fn settings_ui_system(
                         }
                 }
                 SettingsButtonType::Close => {
-                    close_settings(&mut settings_visibility, &mut game_controller);
+                    close_settings(
+                        &mut settings_visibility,
+                        &mut game_controller,
+                        &mut restart_events,
+                        &mut pause_events,
+                        &mut resume_events,
+                    );
                 }
                 SettingsButtonType::Reset => {
                     game_controller.player_stats.high_score = 0;
                     game_controller.save_player_stats(&mut pkv)
                 }
             }
-        } else if interaction != &Interaction::Clicked {
-            button.just_clicked = true;
         }
     }
 
     for (mut text, setting_value_text) in value_query.iter_mut() {
         match setting_value_text.value_type {
-            SettingValueType::MusicVolume => {
+            SettingValueType::Mode => {
+                text.sections[0].value = game_controller.mode.name().to_string();
+            }
+            SettingValueType::Level => {
+                text.sections[0].value = match &game_controller.level {
+                    Some(level) => format!("{}. {}", game_controller.level_index + 1, level.name),
+                    None => format!("{}", game_controller.level_index + 1),
+                };
+            }
+            SettingValueType::Theme => {
+                let index = game_controller.settings.theme % THEMES.len();
                 text.sections[0].value =
-                    format!("{:.0}%", game_controller.settings.music_vol_level * 100.0);
+                    match themes_handler.active(&themes, &game_controller.settings) {
+                        Some(theme) => theme.name.clone(),
+                        None => format!("{}", index + 1),
+                    };
+            }
+            SettingValueType::Sky => {
+                text.sections[0].value = game_controller.settings.sky_mode.name().to_string();
             }
-            SettingValueType::EffectsVolume => {
+            SettingValueType::Weather => {
+                let mode = game_controller.mode;
+
+                text.sections[0].value = match mode {
+                    GameMode::Spectator => Weather::Clear.name().to_string(),
+                    mode if mode.shares_seed() => "Seeded".to_string(),
+                    mode => {
+                        Weather::choice_name(game_controller.settings.weather(mode)).to_string()
+                    }
+                };
+            }
+            SettingValueType::Volume(bus) => {
+                let level = game_controller.settings.volume_level(bus);
                 text.sections[0].value =
-                    format!("{:.0}%", game_controller.settings.effects_vol_level * 100.0);
+                    if bus == AudioBus::Master && game_controller.settings.muted {
+                        "Muted".to_string()
+                    } else {
+                        format!("{:.0}%", level * 100.0)
+                    };
             }
         }
     }
 
     if changed {
-        audio.set_volume(game_controller.settings.music_vol_level);
-
         // update settings in pkv
         pkv.set(GAME_SETTINGS_KEY, &game_controller.settings)
             .expect("Failed to save game settings");
@@ -379,7 +568,6 @@ impl SettingsElement {
                         })
                         .insert(UiZ(33.0))
                         .insert(SettingsButton {
-                            just_clicked: true,
                             button_type: *button_type,
                         });
                 }
@@ -390,16 +578,21 @@ impl SettingsElement {
 
 #[derive(Component)]
 struct SettingsButton {
-    just_clicked: bool,
     button_type: SettingsButtonType,
 }
 
 #[derive(Clone, Copy)]
 enum SettingsButtonType {
-    MusicVolumeMinus,
-    MusicVolumePlus,
-    EffectsVolumeMinus,
-    EffectsVolumePlus,
+    ModeNext,
+    LevelNext,
+    ThemeNext,
+    SkyNext,
+    WeatherNext,
+    Customize,
+    Broadcast,
+    VolumeDown(AudioBus),
+    VolumeUp(AudioBus),
+    Mute,
     FPSShow,
     Close,
     Reset,
@@ -411,8 +604,12 @@ struct SettingValueText {
 }
 
 enum SettingValueType {
-    MusicVolume,
-    EffectsVolume,
+    Mode,
+    Level,
+    Theme,
+    Sky,
+    Weather,
+    Volume(AudioBus),
 }
 
 pub struct SettingsPlugin;
@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::ui::*;
+
+const TOAST_SECS: f32 = 3.5;
+const TOAST_SLIDE_SECS: f32 = 0.4;
+const TOAST_HEIGHT_PERCENT: f32 = 14.0;
+const TOAST_TOP_PERCENT: f32 = 3.0;
+
+/// Short notifications that slide in from the top of the screen one after another
+#[derive(Default)]
+pub struct Toasts {
+    queue: VecDeque<(String, String)>,
+    shown: Option<f32>, // seconds the front toast has been on screen
+}
+
+impl Toasts {
+    pub fn push(&mut self, title: &str, message: &str) {
+        self.queue
+            .push_back((title.to_string(), message.to_string()));
+    }
+}
+
+#[derive(Component)]
+struct ToastUi;
+
+#[derive(Component)]
+struct ToastText {
+    is_title: bool,
+}
+
+fn toast_ui_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_style = TextStyle {
+        font: asset_server.load(FONT_PATH),
+        font_size: 30.0,
+        color: Color::WHITE,
+    };
+
+    let window = UiWindow::new()
+        .with_width_percent(0.4)
+        .with_height_percent(TOAST_HEIGHT_PERCENT / 100.0);
+    window.spawn_with_children(
+        &mut commands,
+        |parent| {
+            SectionHeader::from_title_with(
+                parent,
+                "",
+                TextStyle {
+                    color: Color::GOLD,
+                    ..text_style.clone()
+                },
+                (ToastText { is_title: true },),
+            );
+            SectionHeader::from_title_with(
+                parent,
+                "",
+                TextStyle {
+                    font_size: 25.0,
+                    ..text_style.clone()
+                },
+                (ToastText { is_title: false },),
+            );
+        },
+        ToastUi,
+    );
+}
+
+fn toast_ui_system(
+    mut window_query: Query<(&mut Style, &mut Visibility), With<ToastUi>>,
+    mut text_query: Query<(&mut Text, &ToastText)>,
+    mut toasts: ResMut<Toasts>,
+    time: Res<Time>,
+) {
+    let (mut style, mut visibility) = window_query.single_mut();
+
+    let (title, message) = match toasts.queue.front() {
+        Some(toast) => toast.clone(),
+        None => {
+            visibility.is_visible = false;
+            return;
+        }
+    };
+
+    // real time, so toasts keep moving while the game is paused
+    let shown = toasts.shown.unwrap_or(0.0) + time.delta_seconds();
+    if shown >= TOAST_SECS {
+        toasts.queue.pop_front();
+        toasts.shown = None;
+        return;
+    }
+    toasts.shown = Some(shown);
+
+    // slide in, wait, slide back out
+    let slide = (shown / TOAST_SLIDE_SECS)
+        .min((TOAST_SECS - shown) / TOAST_SLIDE_SECS)
+        .min(1.0);
+    let eased = 1.0 - (1.0 - slide).powi(2);
+    style.position.top =
+        Val::Percent(-TOAST_HEIGHT_PERCENT + (TOAST_HEIGHT_PERCENT + TOAST_TOP_PERCENT) * eased);
+    visibility.is_visible = true;
+
+    for (mut text, toast_text) in text_query.iter_mut() {
+        text.sections[0].value = if toast_text.is_title {
+            title.clone()
+        } else {
+            message.clone()
+        };
+    }
+}
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Toasts>()
+            .add_startup_system(toast_ui_setup)
+            .add_system(toast_ui_system);
+    }
+}
@@ -4,6 +4,7 @@ use crate::ui::*;
 
 pub struct UiWindow {
     width_percent: Option<f32>,
+    height_percent: Option<f32>,
     bg_alpha: Option<f32>,
 }
 
@@ -11,6 +12,7 @@ impl UiWindow {
     pub fn new() -> Self {
         Self {
             width_percent: None,
+            height_percent: None,
             bg_alpha: None,
         }
     }
@@ -20,6 +22,11 @@ impl UiWindow {
         self
     }
 
+    pub fn with_height_percent(mut self, height_percent: f32) -> Self {
+        self.height_percent = Some(height_percent);
+        self
+    }
+
     // pub fn with_bg_alpha(mut self, bg_alpha: f32) -> Self {
     //     self.bg_alpha = Some(bg_alpha);
     //     self
@@ -32,12 +39,13 @@ impl UiWindow {
         window_struct: T,
     ) {
         let window_width = self.width_percent.unwrap_or(0.8) * 100.0;
+        let window_height = self.height_percent.unwrap_or(0.9) * 100.0;
         let bg_alpha = self.bg_alpha.unwrap_or(0.97);
 
         commands
             .spawn_bundle(NodeBundle {
                 style: Style {
-                    size: Size::new(Val::Percent(window_width), Val::Percent(90.0)),
+                    size: Size::new(Val::Percent(window_width), Val::Percent(window_height)),
                     justify_content: JustifyContent::FlexStart,
                     align_items: AlignItems::FlexStart,
                     flex_direction: FlexDirection::ColumnReverse,
@@ -70,6 +78,16 @@ pub struct SectionHeader;
 
 impl SectionHeader {
     pub fn from_title(parent: &mut ChildBuilder, title: &str, style: TextStyle) {
+        Self::from_title_with(parent, title, style, ());
+    }
+
+    /// Same as `from_title`, with `title_bundle` added to the title text
+    pub fn from_title_with(
+        parent: &mut ChildBuilder,
+        title: &str,
+        style: TextStyle,
+        title_bundle: impl Bundle,
+    ) {
         parent
             .spawn_bundle(NodeBundle {
                 style: Style {
@@ -88,7 +106,8 @@ impl SectionHeader {
                         text: Text::from_section(title, style),
                         ..Default::default()
                     })
-                    .insert(UiZ(35.0));
+                    .insert(UiZ(35.0))
+                    .insert_bundle(title_bundle);
             })
             .insert(UiZ(34.0));
     }
@@ -96,14 +115,12 @@ impl SectionHeader {
 
 #[derive(Component, Clone)]
 pub struct UiButton {
-    pub just_clicked: bool,
     pub button_id: String,
 }
 
 impl UiButton {
     pub fn new(button_id: &str) -> Self {
         Self {
-            just_clicked: true,
             button_id: button_id.to_string(),
         }
     }
@@ -0,0 +1,313 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::events::GameReset;
+use crate::ui::{HudText, UiZ};
+use crate::*;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Weather {
+    Clear,
+    Rain,
+    Snow,
+    Wind, // gusts push the bird up or down
+}
+
+impl Weather {
+    pub const ALL: [Weather; 4] = [Weather::Clear, Weather::Rain, Weather::Snow, Weather::Wind];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Weather::Clear => "Clear",
+            Weather::Rain => "Rain",
+            Weather::Snow => "Snow",
+            Weather::Wind => "Wind",
+        }
+    }
+
+    /// Next choice in the settings, `None` rolls the weather from the seed
+    pub fn next_choice(choice: Option<Weather>) -> Option<Weather> {
+        match choice {
+            None => Some(Weather::ALL[0]),
+            Some(weather) => {
+                let index = Weather::ALL.iter().position(|w| *w == weather).unwrap();
+                Weather::ALL.get(index + 1).copied()
+            }
+        }
+    }
+
+    pub fn choice_name(choice: Option<Weather>) -> &'static str {
+        choice.map_or("Random", |weather| weather.name())
+    }
+
+    /// Whether the weather of `mode` can be picked in the settings
+    pub fn selectable(mode: GameMode) -> bool {
+        // spectators only mirror the broadcast
+        !mode.shares_seed() && mode != GameMode::Spectator
+    }
+
+    /// Weather of a run, rolled from its seed so clients sharing a seed get the same one
+    fn of_run(game_controller: &GameController) -> Weather {
+        let mode = game_controller.mode;
+        let choice = match mode {
+            GameMode::Spectator => Some(Weather::Clear),
+            mode if mode.shares_seed() => None,
+            mode => game_controller.settings.weather(mode),
+        };
+
+        choice.unwrap_or_else(|| {
+            let mut rng = StdRng::seed_from_u64(game_controller.seed ^ WEATHER_SEED_SALT);
+            *Weather::ALL.choose(&mut rng).unwrap()
+        })
+    }
+}
+
+/// Weather of the current run and its wind gusts
+struct WeatherState {
+    weather: Weather,
+    seed: u64,
+    rng: StdRng, // seeded from the run, gusts come at the same times in every replay of it
+    spawn_timer: f32,
+    next_gust: f32,           // seconds until the warning of the next gust
+    gust: Option<(f32, f32)>, // seconds left of the warning and the gust, and its force
+}
+
+impl WeatherState {
+    fn new(game_controller: &GameController) -> Self {
+        let mut rng = StdRng::seed_from_u64(game_controller.seed ^ WEATHER_SEED_SALT);
+        let next_gust = rng.gen_range(WIND_GUST_INTERVAL_SECS.0..WIND_GUST_INTERVAL_SECS.1);
+
+        WeatherState {
+            weather: Weather::of_run(game_controller),
+            seed: game_controller.seed,
+            rng,
+            spawn_timer: 0.0,
+            next_gust,
+            gust: None,
+        }
+    }
+
+    /// Force of the gust blowing right now, if it is past its warning
+    fn gust_force(&self) -> Option<f32> {
+        self.gust
+            .filter(|(remaining, _)| *remaining <= WIND_GUST_SECS)
+            .map(|(_, force)| force)
+    }
+}
+
+impl FromWorld for WeatherState {
+    fn from_world(world: &mut World) -> Self {
+        WeatherState::new(world.get_resource::<GameController>().unwrap())
+    }
+}
+
+#[derive(Component)]
+struct WeatherParticle {
+    velocity: Vec2,
+    sway: Option<f32>, // phase of the side to side drift of snow
+}
+
+#[derive(Component)]
+struct WindWarningText;
+
+fn weather_ui_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load(FONT_PATH),
+                    font_size: 30.0,
+                    color: Color::BLACK,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(5.0),
+                    right: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..default()
+            }),
+        )
+        .insert(WindWarningText)
+        .insert(HudText)
+        .insert(UiZ(20.0));
+}
+
+fn weather_system(
+    mut commands: Commands,
+    mut state: ResMut<WeatherState>,
+    mut reset_events: EventReader<GameReset>,
+    mut player_query: Query<&mut Player>,
+    game_controller: Res<GameController>,
+    time: Res<Time>,
+) {
+    // roll again for every run, and whenever the weather setting changed
+    if reset_events.iter().count() > 0
+        || state.seed != game_controller.seed
+        || (game_controller.was_game_waiting()
+            && state.weather != Weather::of_run(&game_controller))
+    {
+        *state = WeatherState::new(&game_controller);
+    }
+
+    if game_controller.is_game_paused() {
+        return;
+    }
+    let delta_time = game_controller.delta_time(&time);
+
+    // rain and snow fall from above the screen, wind streaks only blow during gusts
+    let (rate, spawn_y) = match state.weather {
+        Weather::Clear => (0.0, 0.0),
+        Weather::Rain => (RAIN_PER_SEC, SCREEN_Y_BOUNDARY + 20.0),
+        Weather::Snow => (SNOW_PER_SEC, SCREEN_Y_BOUNDARY + 20.0),
+        Weather::Wind if state.gust_force().is_some() => (WIND_STREAKS_PER_SEC, 0.0),
+        Weather::Wind => (0.0, 0.0),
+    };
+    state.spawn_timer += delta_time * rate;
+    let mut rng = thread_rng();
+    while state.spawn_timer >= 1.0 {
+        state.spawn_timer -= 1.0;
+        spawn_particle(&mut commands, &mut rng, state.weather, spawn_y);
+    }
+
+    if state.weather != Weather::Wind || !game_controller.is_game_running() {
+        return;
+    }
+
+    // wind gusts, warned about before they blow
+    match state.gust {
+        Some((remaining, force)) => {
+            let remaining = remaining - delta_time;
+            state.gust = (remaining > 0.0).then(|| (remaining, force));
+        }
+        None => {
+            state.next_gust -= delta_time;
+
+            if state.next_gust <= 0.0 {
+                let force = match state.rng.gen_bool(0.5) {
+                    true => WIND_GUST_FORCE,
+                    false => -WIND_GUST_FORCE,
+                };
+                state.gust = Some((WIND_WARNING_SECS + WIND_GUST_SECS, force));
+                state.next_gust = state
+                    .rng
+                    .gen_range(WIND_GUST_INTERVAL_SECS.0..WIND_GUST_INTERVAL_SECS.1);
+            }
+        }
+    }
+
+    if let Some(force) = state.gust_force() {
+        let mut player = player_query.single_mut();
+        if !player.dead {
+            player.delta_y += force * delta_time;
+        }
+    }
+}
+
+fn spawn_particle(commands: &mut Commands, rng: &mut ThreadRng, weather: Weather, y: f32) {
+    let (size, color, velocity, y) = match weather {
+        Weather::Rain => (
+            Vec2::new(2.0, 14.0),
+            Color::rgba(0.75, 0.85, 1.0, 0.6),
+            Vec2::new(-60.0, -900.0),
+            y,
+        ),
+        Weather::Snow => (
+            Vec2::splat(rng.gen_range(3.0..6.0)),
+            Color::rgba(1.0, 1.0, 1.0, 0.9),
+            Vec2::new(-40.0, -rng.gen_range(60.0..120.0)),
+            y,
+        ),
+        _ => (
+            Vec2::new(rng.gen_range(30.0..70.0), 2.0),
+            Color::rgba(1.0, 1.0, 1.0, 0.4),
+            Vec2::new(-1400.0, 0.0),
+            rng.gen_range(-SCREEN_Y_BOUNDARY..SCREEN_Y_BOUNDARY),
+        ),
+    };
+    let x = match weather {
+        Weather::Wind => SCREEN_X_BOUNDARY,
+        _ => rng.gen_range(-SCREEN_X_BOUNDARY..SCREEN_X_BOUNDARY),
+    };
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(size),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(x, y, Z_WEATHER),
+            ..Default::default()
+        })
+        .insert(WeatherParticle {
+            velocity,
+            sway: (weather == Weather::Snow).then(|| rng.gen_range(0.0..std::f32::consts::TAU)),
+        });
+}
+
+fn weather_particles_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut WeatherParticle, &mut Transform)>,
+    game_controller: Res<GameController>,
+    time: Res<Time>,
+) {
+    if game_controller.is_game_paused() {
+        return;
+    }
+    let delta_time = game_controller.delta_time(&time);
+
+    for (entity, mut particle, mut transform) in query.iter_mut() {
+        transform.translation += particle.velocity.extend(0.0) * delta_time;
+
+        if let Some(sway) = particle.sway.as_mut() {
+            *sway += delta_time * 2.0;
+            transform.translation.x += sway.sin() * 30.0 * delta_time;
+        }
+
+        if transform.translation.y < -SCREEN_Y_BOUNDARY - 20.0
+            || transform.translation.x < -SCREEN_X_BOUNDARY
+        {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn wind_warning_ui_system(
+    mut text_query: Query<&mut Text, With<WindWarningText>>,
+    state: Res<WeatherState>,
+    time: Res<Time>,
+) {
+    let mut text = text_query.single_mut();
+
+    text.sections[0].value = match state.gust {
+        // blink while warning, then show where the wind pushes
+        Some((remaining, force)) if remaining > WIND_GUST_SECS => {
+            let blink = (time.seconds_since_startup() * 4.0) as u32 % 2 == 0;
+            let direction = if force > 0.0 { "up" } else { "down" };
+
+            match blink {
+                true => format!("Gust incoming: {}!", direction),
+                false => "".to_string(),
+            }
+        }
+        Some((_, force)) => format!("Wind {}!", if force > 0.0 { "up" } else { "down" }),
+        None => "".to_string(),
+    };
+}
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WeatherState>()
+            .add_startup_system(weather_ui_setup)
+            .add_system(weather_system)
+            .add_system(weather_particles_system)
+            .add_system(wind_warning_ui_system);
+    }
+}